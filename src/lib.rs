@@ -0,0 +1,74 @@
+//! finli reads receipts named `[DATE]-[VENDOR]-[COST]-[DESCRIPTION]-[CATEGORY]-[LOCATION].pdf`,
+//! sorts them into per-location folders, splitting shared receipts between locations, and
+//! renders invoices from them. The `finli` binary is a thin CLI over this crate.
+//!
+//! ```no_run
+//! use finli::{PdfDuplicateOptions, PdfInvoice, PdfReadOptions, PdfSortOptions, PdfSortPlan};
+//!
+//! let invoice = PdfInvoice::new_from_dir("./receipts", "march", &PdfReadOptions::default(), &PdfDuplicateOptions::default(), &Default::default()).unwrap();
+//! println!("{} total", invoice.total_cost);
+//!
+//! let plan = PdfSortPlan::new("./receipts", "./sorted", &PdfSortOptions::default()).unwrap();
+//! println!("{}", plan.to_table());
+//! ```
+//!
+//! Every fallible call returns a `String` error of the form `"CATEGORY: message"`.
+
+/// sharing an amount out between parties to the cent
+pub mod pdf_allocate;
+/// bringing a scanned receipt into an inbox under its canonical name
+pub mod pdf_add;
+/// spending against monthly budgets per location and category
+pub mod pdf_budget;
+/// bar and line charts drawn straight into a pdf
+pub mod pdf_chart;
+/// validating a dir of receipts and auditing them against their text
+pub mod pdf_check;
+/// the `finli.toml` config
+pub mod pdf_config;
+/// writing csv reports
+pub mod pdf_csv;
+/// finding receipts that were scanned more than once
+pub mod pdf_duplicates;
+/// content hashes of receipts
+pub mod pdf_hash;
+/// `.finliignore` files
+pub mod pdf_ignore;
+/// photographed receipts and turning them into pdfs
+pub mod pdf_image;
+/// parsing receipts into line items and rendering invoices
+pub mod pdf_invoice;
+/// calendar months, quarters and years to report over
+pub mod pdf_period;
+/// comparing a period's totals with earlier periods
+pub mod pdf_report;
+/// per person expense reports for staff who paid out of pocket
+pub mod pdf_reimburse;
+/// bringing loosely named receipts to canonical names, with undo
+pub mod pdf_rename;
+/// who owes whom for shared expenses between locations
+pub mod pdf_settle;
+/// optional metadata files next to a receipt
+pub mod pdf_sidecar;
+/// what earlier sorts of an output dir did, for incremental sorts
+pub mod pdf_sort_manifest;
+/// working out a sort, splits included, without touching disk
+pub mod pdf_sort_plan;
+/// the per-location summaries a sort writes
+pub mod pdf_sort_summary;
+/// carrying out a sort plan with rollback
+pub mod pdf_sorted_dir;
+/// suggesting receipt fields from the receipt text
+pub mod pdf_suggest;
+/// reading the text layer of a receipt
+pub mod pdf_text;
+/// checking that receipts really open
+pub mod pdf_verify;
+/// sorting receipts as they land in an inbox
+pub mod pdf_watch;
+
+pub use pdf_config::PdfConfig;
+pub use pdf_duplicates::{PdfDuplicateOptions, PdfDuplicateReport};
+pub use pdf_invoice::{PdfExpenseCategory, PdfInvoice, PdfLineItem, PdfReadOptions};
+pub use pdf_sort_plan::{PdfConflictPolicy, PdfSortMode, PdfSortOptions, PdfSortPlan};
+pub use pdf_sorted_dir::PdfSortedDir;
@@ -1,61 +1,589 @@
-use std::fs;
+use std::time::Duration;
 
 use clap::Parser;
 use clap::Subcommand;
 
-use crate::pdf_invoice::PdfInvoice;
-use crate::pdf_sorted_dir::PdfSortedDir;
-
-mod pdf_invoice;
-mod pdf_sorted_dir;
+use finli::pdf_add::{PdfKnownValues, PdfReceiptDraft};
+use finli::pdf_budget::PdfBudgetReport;
+use finli::pdf_check::PdfCheckReport;
+use finli::pdf_config::PdfConfig;
+use finli::pdf_duplicates::{PdfDuplicateOptions, PdfDuplicateReport, DEFAULT_DUPLICATE_DAYS};
+use finli::pdf_invoice::{PdfInvoice, PdfLineItem, PdfReadOptions};
+use finli::pdf_period::PdfPeriod;
+use finli::pdf_reimburse::PdfReimbursement;
+use finli::pdf_rename::{PdfRenameJournal, PdfRenamePlan, DEFAULT_JOURNAL_FILE};
+use finli::pdf_report::{PdfComparisonReport, PdfReportGroup};
+use finli::pdf_settle::{PdfSettlePeriod, PdfSettlement};
+use finli::pdf_sort_plan::{PdfSortOptions, PdfSortPlan, DEFAULT_LAYOUT};
+use finli::pdf_sorted_dir::PdfSortedDir;
+use finli::pdf_suggest::PdfSuggestion;
+use finli::pdf_watch::PdfWatcher;
 
 #[derive(Parser, Debug)]
 #[command(name = "", about = "", version = "1.0")]
 struct Args {
     #[command(subcommand)]
     command: Command,
+    #[arg(long, global = true)]
+    config: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
-    Generate { dir: String, invoice_name: String },
-    Sort { dir: String, out: String },
+    Generate {
+        dir: String,
+        invoice_name: String,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_DAYS)]
+        duplicate_days: i64,
+        #[arg(long)]
+        exclude_duplicates: bool,
+        #[arg(long)]
+        charts: bool,
+    },
+    Duplicates {
+        dir: String,
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_DAYS)]
+        days: i64,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+    },
+    Sort {
+        dir: String,
+        out: String,
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long, value_parser = ["table", "json"], default_value = "table")]
+        format: String,
+        #[arg(long, value_parser = ["copy", "move", "hardlink", "reflink", "symlink"], default_value = "copy")]
+        mode: String,
+        #[arg(long, value_parser = ["skip", "overwrite", "rename", "fail"], default_value = "fail")]
+        on_conflict: String,
+        #[arg(long)]
+        full: bool,
+        #[arg(long, default_value = DEFAULT_LAYOUT)]
+        layout: String,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_DAYS)]
+        duplicate_days: i64,
+        #[arg(long)]
+        exclude_duplicates: bool,
+        #[arg(long)]
+        convert_images: bool,
+    },
+    Rename {
+        dir: String,
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        undo: bool,
+        #[arg(long)]
+        suggest: bool,
+        #[arg(long, default_value = DEFAULT_JOURNAL_FILE)]
+        journal: String,
+    },
+    Suggest { dir: String },
+    Check {
+        dir: String,
+        #[arg(long)]
+        audit: bool,
+        #[arg(long)]
+        report: Option<String>,
+    },
+    Add {
+        scanned: String,
+        inbox: String,
+        #[arg(long)]
+        date: Option<String>,
+        #[arg(long)]
+        vendor: Option<String>,
+        #[arg(long)]
+        cost: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        category: Option<String>,
+        #[arg(long)]
+        location: Option<String>,
+        #[arg(long = "move")]
+        move_file: bool,
+    },
+    Watch {
+        inbox: String,
+        out: String,
+        #[arg(long, value_parser = ["copy", "move", "hardlink", "reflink", "symlink"], default_value = "move")]
+        mode: String,
+        #[arg(long, value_parser = ["skip", "overwrite", "rename", "fail"], default_value = "fail")]
+        on_conflict: String,
+        #[arg(long, default_value_t = 2)]
+        settle: u64,
+        #[arg(long, default_value = DEFAULT_LAYOUT)]
+        layout: String,
+        #[arg(long)]
+        convert_images: bool,
+    },
+    Reimburse {
+        dir: String,
+        #[arg(long)]
+        submitter: Option<String>,
+        #[arg(long, default_value = "reimbursement")]
+        name: String,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_DAYS)]
+        duplicate_days: i64,
+        #[arg(long)]
+        exclude_duplicates: bool,
+        #[arg(long)]
+        charts: bool,
+    },
+    Budget {
+        dir: String,
+        #[arg(long)]
+        period: Option<String>,
+        #[arg(long, default_value = "budget")]
+        name: String,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+    },
+    Report {
+        dir: String,
+        #[arg(long)]
+        period: Option<String>,
+        #[arg(long, value_parser = ["category", "location"], default_value = "category")]
+        group: String,
+        #[arg(long, default_value = "report")]
+        name: String,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+    },
+    Settle {
+        dir: String,
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(long, default_value = "settlement")]
+        name: String,
+        #[arg(long)]
+        recursive: bool,
+        #[arg(long)]
+        skip_invalid: bool,
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_DAYS)]
+        duplicate_days: i64,
+        #[arg(long)]
+        exclude_duplicates: bool,
+    },
 }
 
-fn run_generate(dir: String, invoice_name: String) -> Option<String> {
-    let invoice = PdfInvoice::new_from_dir(&dir, &invoice_name);
+fn run_generate(config: &PdfConfig, dir: String, invoice_name: String, read: PdfReadOptions, duplicates: PdfDuplicateOptions, charts: bool) -> Option<String> {
+    let invoice = PdfInvoice::new_from_dir(&dir, &invoice_name, &read, &duplicates, &config.split);
     if invoice.is_err() {
         let err = invoice.err().unwrap();
         return Some(err);
     }
-    let invoice = invoice.unwrap();
+    let mut invoice = invoice.unwrap();
+    invoice.charts = charts;
     let err = invoice.generate();
     if err.is_some() {
         let err = err.unwrap();
         return Some(err);
     }
+    for skipped in &invoice.skipped {
+        println!("SKIPPED: {}: {}", skipped.path, skipped.reason);
+    }
+    print!("{}", invoice.duplicates.to_text());
+    for excluded in &invoice.excluded {
+        println!("excluded duplicate {}", excluded);
+    }
     return None;
 }
 
-fn run_sort(dir: String, out: String) -> Result<(), String> {
-    let sorted_dir = PdfSortedDir::new(&dir, &out)?;
+fn run_duplicates(dir: String, days: i64, read: PdfReadOptions) -> Result<(), String> {
+    let (line_items, skipped) = PdfLineItem::new_from_dir(&dir, &read)?;
+    let report = PdfDuplicateReport::new(&line_items, days)?;
+    print!("{}", report.to_text());
+    for skipped in &skipped {
+        println!("SKIPPED: {}: {}", skipped.path, skipped.reason);
+    }
+    println!("{} duplicate groups in {} receipts, {} confirmed duplicates", report.groups.len(), line_items.len(), report.confirmed().len());
+    return Ok(());
+}
+
+fn sort_options(mode: &str, on_conflict: &str, full: bool, layout: String, convert_images: bool, read: PdfReadOptions, duplicates: PdfDuplicateOptions) -> Result<PdfSortOptions, String> {
+    let options = PdfSortOptions {
+        mode: mode.parse()?,
+        on_conflict: on_conflict.parse()?,
+        full: full,
+        layout: layout,
+        convert_images: convert_images,
+        read: read,
+        duplicates: duplicates,
+        ..PdfSortOptions::default()
+    };
+    return Ok(options);
+}
+
+fn run_sort(dir: String, out: String, dry_run: bool, format: String, options: PdfSortOptions) -> Result<(), String> {
+    if dry_run {
+        let plan = PdfSortPlan::new(&dir, &out, &options)?;
+        if format == "json" {
+            println!("{}", plan.to_json()?);
+        } else {
+            print!("{}", plan.to_table());
+        }
+        return Ok(());
+    }
+    let sorted_dir = PdfSortedDir::new(&dir, &out, &options)?;
+    println!("sorted receipts from {} into {}", dir, sorted_dir.dir_root);
+    for dir in &sorted_dir.dirs {
+        println!("{}", dir);
+    }
+    for file in &sorted_dir.files {
+        println!("  {}", file);
+    }
+    for conflict in &sorted_dir.conflicts {
+        println!("conflict {} -> {}: {}, {}", conflict.source, conflict.destination, conflict.reason, conflict.resolution);
+    }
+    for removed in &sorted_dir.removed {
+        println!("removed from source {}", removed);
+    }
+    for kept in &sorted_dir.kept {
+        println!("kept in source {}, not every share of the split was written", kept);
+    }
+    let summary = &sorted_dir.summary;
+    for location in &summary.locations {
+        println!("{}: {} receipts, {} total", location.location, location.count, location.total);
+    }
+    if !summary.splits.is_empty() {
+        println!("splits shared with {}", summary.split);
+    }
+    for split in &summary.splits {
+        let allocations: Vec<String> = split
+            .allocations
+            .iter()
+            .map(|allocation| format!("{} {}", allocation.location, allocation.cost))
+            .collect();
+        println!("split {} ({}) => {}", split.source, split.cost, allocations.join(", "));
+    }
+    for skipped in &summary.skipped {
+        println!("skipped {}: {}", skipped.source, skipped.reason);
+    }
+    println!("{} receipts unchanged since the last sort", summary.unchanged.len());
+    for path in &sorted_dir.summary_files {
+        println!("wrote {}", path);
+    }
+    for invalid in &sorted_dir.invalid {
+        println!("SKIPPED: {}: {}", invalid.path, invalid.reason);
+    }
+    print!("{}", sorted_dir.duplicates.to_text());
+    for excluded in &sorted_dir.excluded {
+        println!("excluded duplicate {}", excluded);
+    }
+    return Ok(());
+}
+
+fn run_watch(config: &PdfConfig, inbox: String, out: String, options: PdfSortOptions, settle: u64) -> Result<(), String> {
+    let mut watcher = PdfWatcher::new(&inbox, &out, options, config.verify.clone(), Duration::from_secs(settle))?;
+    return watcher.run();
+}
+
+fn run_rename(config: &PdfConfig, dir: String, dry_run: bool, undo: bool, suggest: bool, journal: String) -> Result<(), String> {
+    if undo {
+        let run = PdfRenameJournal::undo(&journal, &dir)?;
+        for rename in &run.renames {
+            println!("{} -> {}", rename.to, rename.from);
+        }
+        println!("restored {} files", run.renames.len());
+        return Ok(());
+    }
+
+    let plan = PdfRenamePlan::new(&dir, config, suggest)?;
+    for rename in &plan.renames {
+        println!("{} -> {}", rename.from, rename.to);
+    }
+    for failure in &plan.failures {
+        println!("SKIPPED: {}", failure);
+    }
+    for mismatch in &plan.mismatches {
+        println!("{}", mismatch);
+    }
+    if dry_run {
+        println!("dry run: {} files would be renamed, {} already canonical", plan.renames.len(), plan.unchanged.len());
+        return Ok(());
+    }
+    plan.execute(&journal)?;
+    println!("renamed {} files, {} already canonical, journal written to {}", plan.renames.len(), plan.unchanged.len(), journal);
+    return Ok(());
+}
+
+fn run_add(config: &PdfConfig, scanned: String, inbox: String, mut draft: PdfReceiptDraft, move_file: bool) -> Result<(), String> {
+    let known = PdfKnownValues::new_from_dir(&inbox);
+    let suggestion = PdfSuggestion::new(&scanned, config, &known.vendors).ok();
+    draft.prompt_missing(&known, suggestion.as_ref())?;
+    let line_item = draft.add(&scanned, &inbox, config, move_file)?;
+    println!("added {}", line_item.path);
+    return Ok(());
+}
+
+fn run_suggest(config: &PdfConfig, dir: String) -> Result<(), String> {
+    let suggestions = PdfSuggestion::new_from_dir(&dir, config)?;
+    let none = "?".to_owned();
+    for suggestion in &suggestions {
+        if suggestion.error.is_some() {
+            println!("{}\n    {}", suggestion.path, suggestion.error.as_ref().unwrap());
+            continue;
+        }
+        if !suggestion.has_text {
+            println!("{}\n    no text layer, nothing to suggest", suggestion.path);
+            continue;
+        }
+        println!("{}", suggestion.path);
+        println!("    date:   {}", suggestion.date.as_ref().unwrap_or(&none));
+        println!("    vendor: {}", suggestion.vendor.as_ref().unwrap_or(&none));
+        println!("    total:  {}", suggestion.total.map(|total| format!("{:.2}", total)).unwrap_or(none.clone()));
+        if suggestion.amount_mismatch() {
+            println!("    AMOUNT MISMATCH: the name says {} but the document shows {}", suggestion.file_cost.unwrap(), suggestion.total.unwrap());
+        }
+    }
+    let mismatches = suggestions.iter().filter(|suggestion| suggestion.amount_mismatch()).count();
+    println!("read {} receipts, {} with a mismatched amount", suggestions.len(), mismatches);
+    return Ok(());
+}
+
+fn run_check(config: &PdfConfig, dir: String, audit: bool, report: Option<String>) -> Result<(), String> {
+    let check = PdfCheckReport::new(&dir, audit, &config.verify)?;
+    for verification in &check.verifications {
+        let pages = match verification.pages {
+            Some(1) => "1 page".to_owned(),
+            Some(pages) => format!("{} pages", pages),
+            None => "page count unknown".to_owned(),
+        };
+        let encrypted = if verification.encrypted { ", encrypted" } else { "" };
+        println!("{}: {}{}", verification.path, pages, encrypted);
+    }
+    for issue in &check.issues {
+        println!("{}\n    {}", issue.path, issue.message.replace("\n", "\n    "));
+    }
+    for mismatch in &check.mismatches {
+        println!("{}\n    AUDIT MISMATCH: {} (date {}, cost {})", mismatch.path, mismatch.reason(), mismatch.date, mismatch.cost);
+    }
+    if report.is_some() {
+        let report = report.unwrap();
+        check.write_mismatch_csv(&report)?;
+        println!("mismatch report written to {}", report);
+    }
+    let pages: usize = check.verifications.iter().filter_map(|verification| verification.pages).sum();
+    println!("checked {} valid receipts ({} pages), {} problems, {} audit mismatches", check.line_items.len(), pages, check.issues.len(), check.mismatches.len());
+    if !check.issues.is_empty() || !check.mismatches.is_empty() {
+        return Err(format!("CHECK FAILED: {} has receipts that need attention", dir));
+    }
+    return Ok(());
+}
+
+fn run_reimburse(config: &PdfConfig, dir: String, submitter: Option<String>, name: String, read: PdfReadOptions, duplicates: PdfDuplicateOptions, charts: bool) -> Result<(), String> {
+    let (mut reimbursements, skipped) = PdfReimbursement::new_from_dir(&dir, &name, &read, &duplicates, &config.split)?;
+    for reimbursement in reimbursements.iter_mut() {
+        reimbursement.invoice.charts = charts;
+    }
+    if submitter.is_some() {
+        let submitter = submitter.unwrap().to_lowercase();
+        reimbursements.retain(|reimbursement| reimbursement.submitter == submitter);
+        if reimbursements.is_empty() {
+            return Err(format!("UNKNOWN SUBMITTER: no receipts in {} were submitted by '{}'", dir, submitter));
+        }
+    }
+    for skipped in &skipped {
+        println!("SKIPPED: {}: {}", skipped.path, skipped.reason);
+    }
+    for reimbursement in &reimbursements {
+        reimbursement.generate()?;
+        print!("{}", reimbursement.invoice.duplicates.to_text());
+        println!("{} is owed {} for {} receipts, written to {}", reimbursement.submitter, reimbursement.invoice.total_cost, reimbursement.receipts().len(), reimbursement.invoice.file_name);
+    }
+    if reimbursements.is_empty() {
+        println!("no receipts in {} have a 'submitter' sidecar field", dir);
+    }
+    return Ok(());
+}
+
+fn run_budget(config: &PdfConfig, dir: String, period: Option<String>, name: String, read: PdfReadOptions) -> Result<(), String> {
+    let period = match period {
+        Some(period) => Some(period.parse::<PdfPeriod>()?),
+        None => None,
+    };
+    let report = PdfBudgetReport::new(&dir, period, &read, config)?;
+    let file_name = name.to_lowercase().replace(" ", "_");
+    report.write_pdf(&format!("{}.pdf", file_name))?;
+    report.write_csv(&format!("{}.csv", file_name))?;
+    report.write_json(&format!("{}.json", file_name))?;
+    for skipped in &report.skipped {
+        println!("SKIPPED: {}: {}", skipped.path, skipped.reason);
+    }
+    for location in &report.locations {
+        println!("{}", location.location);
+        for category in &location.categories {
+            println!("    {}", category.describe());
+        }
+        println!("    {}", location.total.describe());
+    }
+    let over_budget = report.over_budget();
+    for over in &over_budget {
+        println!("OVER BUDGET: {}", over);
+    }
+    println!("budget for {}, {} categories over budget, written to {}.pdf, {}.csv and {}.json", report.period, over_budget.len(), file_name, file_name, file_name);
+    return Ok(());
+}
+
+fn run_report(config: &PdfConfig, dir: String, period: Option<String>, group: String, name: String, read: PdfReadOptions) -> Result<(), String> {
+    let period = match period {
+        Some(period) => Some(period.parse::<PdfPeriod>()?),
+        None => None,
+    };
+    let report = PdfComparisonReport::new(&dir, period, group.parse::<PdfReportGroup>()?, &read, config)?;
+    let file_name = name.to_lowercase().replace(" ", "_");
+    report.write_pdf(&format!("{}.pdf", file_name))?;
+    report.write_csv(&format!("{}.csv", file_name))?;
+    report.write_json(&format!("{}.json", file_name))?;
+    for skipped in &report.skipped {
+        println!("SKIPPED: {}: {}", skipped.path, skipped.reason);
+    }
+    for line in report.lines.iter().chain([&report.total]) {
+        println!("{}", report.describe(line));
+    }
+    println!("{} by {} against {} and {}, written to {}.pdf, {}.csv and {}.json", report.period, report.group, report.previous_period, report.year_earlier_period, file_name, file_name, file_name);
+    return Ok(());
+}
+
+fn run_settle(config: &PdfConfig, dir: String, period: PdfSettlePeriod, name: String, read: PdfReadOptions, duplicates: PdfDuplicateOptions) -> Result<(), String> {
+    let settlement = PdfSettlement::new(&dir, &period, &read, &duplicates, config)?;
+    let file_name = name.to_lowercase().replace(" ", "_");
+    settlement.write_csv(&format!("{}.csv", file_name))?;
+    settlement.write_pdf(&format!("{}.pdf", file_name))?;
+    for skipped in &settlement.skipped {
+        println!("SKIPPED: {}: {}", skipped.path, skipped.reason);
+    }
+    for excluded in &settlement.excluded {
+        println!("excluded duplicate {}", excluded);
+    }
+    for path in &settlement.unknown_payer {
+        println!("NO PAYER: {}: add a 'paid_by' or 'card' sidecar field", path);
+    }
+    for balance in &settlement.balances {
+        println!("{} owes {} {:.2}", balance.debtor, balance.creditor, balance.amount);
+    }
+    if settlement.balances.is_empty() {
+        println!("nothing is owed between locations");
+    }
+    println!("settled {} receipts for {}, written to {}.pdf and {}.csv", settlement.receipts.len(), period.describe(), file_name, file_name);
     return Ok(());
 }
 
 fn main() {
     let args = Args::parse();
+    let config = PdfConfig::load(args.config.as_deref());
+    if config.is_err() {
+        panic!("{}", config.err().unwrap());
+    }
+    let config = config.unwrap();
     match args.command {
-        Command::Generate { dir, invoice_name } => {
-            let err = run_generate(dir, invoice_name);
+        Command::Generate { dir, invoice_name, recursive, skip_invalid, duplicate_days, exclude_duplicates, charts } => {
+            let read = PdfReadOptions { recursive, skip_invalid, verify: Some(config.verify.clone()) };
+            let duplicates = PdfDuplicateOptions { days: duplicate_days, exclude: exclude_duplicates };
+            let err = run_generate(&config, dir, invoice_name, read, duplicates, charts);
             if err.is_some() {
                 panic!("{}", err.unwrap());
             }
         },
-        Command::Sort { dir, out } => {
-            let err = run_sort(dir, out);
+        Command::Duplicates { dir, days, recursive, skip_invalid } => {
+            let err = run_duplicates(dir, days, PdfReadOptions { recursive, skip_invalid, verify: None });
             if err.is_err() {
-                panic!("{:?}", err.unwrap());
+                panic!("{}", err.err().unwrap());
             }
-        }
+        },
+        Command::Sort { dir, out, dry_run, format, mode, on_conflict, full, layout, recursive, skip_invalid, duplicate_days, exclude_duplicates, convert_images } => {
+            let read = PdfReadOptions { recursive, skip_invalid, verify: Some(config.verify.clone()) };
+            let duplicates = PdfDuplicateOptions { days: duplicate_days, exclude: exclude_duplicates };
+            let err = sort_options(&mode, &on_conflict, full, layout, convert_images, read, duplicates)
+                .map(|options| PdfSortOptions { split: config.split.clone(), ..options })
+                .and_then(|options| run_sort(dir, out, dry_run, format, options));
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Rename { dir, dry_run, undo, suggest, journal } => {
+            let err = run_rename(&config, dir, dry_run, undo, suggest, journal);
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Add { scanned, inbox, date, vendor, cost, description, category, location, move_file } => {
+            let draft = PdfReceiptDraft { date, vendor, cost, description, category, location };
+            let err = run_add(&config, scanned, inbox, draft, move_file);
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Suggest { dir } => {
+            let err = run_suggest(&config, dir);
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Check { dir, audit, report } => {
+            let err = run_check(&config, dir, audit, report);
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Watch { inbox, out, mode, on_conflict, settle, layout, convert_images } => {
+            let err = sort_options(&mode, &on_conflict, false, layout, convert_images, PdfReadOptions::default(), PdfDuplicateOptions::default())
+                .map(|options| PdfSortOptions { split: config.split.clone(), ..options })
+                .and_then(|options| run_watch(&config, inbox, out, options, settle));
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Reimburse { dir, submitter, name, recursive, skip_invalid, duplicate_days, exclude_duplicates, charts } => {
+            let read = PdfReadOptions { recursive, skip_invalid, verify: Some(config.verify.clone()) };
+            let duplicates = PdfDuplicateOptions { days: duplicate_days, exclude: exclude_duplicates };
+            let err = run_reimburse(&config, dir, submitter, name, read, duplicates, charts);
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Budget { dir, period, name, recursive, skip_invalid } => {
+            let err = run_budget(&config, dir, period, name, PdfReadOptions { recursive, skip_invalid, verify: None });
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Report { dir, period, group, name, recursive, skip_invalid } => {
+            let err = run_report(&config, dir, period, group, name, PdfReadOptions { recursive, skip_invalid, verify: None });
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
+        Command::Settle { dir, from, to, name, recursive, skip_invalid, duplicate_days, exclude_duplicates } => {
+            let duplicates = PdfDuplicateOptions { days: duplicate_days, exclude: exclude_duplicates };
+            let err = run_settle(&config, dir, PdfSettlePeriod { from, to }, name, PdfReadOptions { recursive, skip_invalid, verify: None }, duplicates);
+            if err.is_err() {
+                panic!("{}", err.err().unwrap());
+            }
+        },
     }
 }
@@ -0,0 +1,246 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::pdf_config::PdfConfig;
+use crate::pdf_invoice::{is_receipt_path, PdfLineItem, VALID_LOCATIONS};
+use crate::pdf_rename::{normalize_cost, normalize_date};
+use crate::pdf_sidecar::{PdfSidecar, PdfSidecarFormat};
+use crate::pdf_suggest::PdfSuggestion;
+use crate::pdf_verify::PdfVerification;
+
+/// the fields that make up a receipt name, any left empty are prompted for
+#[derive(Debug, Default, Clone)]
+pub struct PdfReceiptDraft {
+    pub date: Option<String>,
+    pub vendor: Option<String>,
+    pub cost: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub location: Option<String>,
+}
+
+/// values already used by receipts in the inbox, offered as completions
+#[derive(Debug, Default)]
+pub struct PdfKnownValues {
+    pub vendors: Vec<String>,
+    pub descriptions: Vec<String>,
+    pub categories: Vec<String>,
+    pub locations: Vec<String>,
+}
+
+impl PdfKnownValues {
+
+    /// unreadable or badly named files are skipped, this is only a source of suggestions
+    pub fn new_from_dir(dir: &str) -> PdfKnownValues {
+        let mut known = PdfKnownValues {
+            locations: VALID_LOCATIONS.iter().map(|location| location.trim_end_matches(".pdf").to_owned()).collect(),
+            ..Default::default()
+        };
+        let entries = fs::read_dir(dir);
+        if entries.is_err() {
+            return known;
+        }
+        for entry in entries.unwrap().flatten() {
+            let path = entry.path();
+            if !is_receipt_path(&path) {
+                continue;
+            }
+            let path_str = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+            let item = PdfLineItem::new(dir, &path_str);
+            if item.is_err() {
+                continue;
+            }
+            let item = item.unwrap();
+            PdfKnownValues::remember(&mut known.vendors, item.vendor);
+            PdfKnownValues::remember(&mut known.descriptions, item.description);
+            PdfKnownValues::remember(&mut known.categories, item.category);
+        }
+        known.vendors.sort();
+        known.descriptions.sort();
+        known.categories.sort();
+        return known;
+    }
+
+    fn remember(values: &mut Vec<String>, value: String) {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+
+}
+
+impl PdfReceiptDraft {
+
+    /// asks for every missing field on stdin, suggestions read from the scan are offered as defaults
+    pub fn prompt_missing(&mut self, known: &PdfKnownValues, suggestion: Option<&PdfSuggestion>) -> Result<(), String> {
+        let suggested_date = suggestion.and_then(|suggestion| suggestion.date.clone());
+        let suggested_vendor = suggestion.and_then(|suggestion| suggestion.vendor.clone());
+        let suggested_cost = suggestion.and_then(|suggestion| suggestion.total.map(|total| format!("{:.2}", total)));
+        if self.date.is_none() {
+            self.date = Some(prompt("date (MMDDYY)", &[], suggested_date)?);
+        }
+        if self.vendor.is_none() {
+            self.vendor = Some(prompt("vendor", &known.vendors, suggested_vendor)?);
+        }
+        if self.cost.is_none() {
+            self.cost = Some(prompt("cost", &[], suggested_cost)?);
+        }
+        if self.description.is_none() {
+            self.description = Some(prompt("description", &known.descriptions, None)?);
+        }
+        if self.category.is_none() {
+            self.category = Some(prompt("category", &known.categories, None)?);
+        }
+        if self.location.is_none() {
+            self.location = Some(prompt("location", &known.locations, None)?);
+        }
+        return Ok(());
+    }
+
+    /// builds the canonical file name, applying the same normalization as `finli rename`,
+    /// `ext` is the extension of the scan being added
+    pub fn file_name(&self, config: &PdfConfig, ext: &str) -> Result<String, String> {
+        let field = |value: &Option<String>, name: &str| -> Result<String, String> {
+            let value = value.clone().unwrap_or_default().trim().to_lowercase();
+            if value.is_empty() {
+                return Err(format!("MISSING FIELD: the receipt '{}' field is required", name));
+            }
+            if value.contains("-") {
+                return Err(format!("INVALID FIELD: the receipt '{}' field cannot contain '-'\n{}", name, value));
+            }
+            return Ok(value);
+        };
+
+        let date = field(&self.date, "date")?;
+        let date = normalize_date(&date);
+        if date.is_none() {
+            return Err("INVALID DATE: the receipt 'date' field should look like '010125'".to_owned());
+        }
+        let cost = field(&self.cost, "cost")?;
+        let cost = normalize_cost(&cost);
+        if cost.is_none() {
+            return Err("INVALID COST: the receipt 'cost' field should be an amount with at most two decimals".to_owned());
+        }
+
+        let file_name = format!(
+            "{}-{}-{}-{}-{}-{}.{}",
+            date.unwrap(),
+            config.aliases.vendor(&field(&self.vendor, "vendor")?),
+            cost.unwrap(),
+            field(&self.description, "description")?,
+            config.aliases.category(&field(&self.category, "category")?),
+            config.aliases.location(&field(&self.location, "location")?),
+            ext,
+        );
+        return Ok(file_name);
+    }
+
+    /// validates the draft and brings the scan into the inbox under its canonical name
+    pub fn add(&self, scanned: &str, inbox: &str, config: &PdfConfig, move_file: bool) -> Result<PdfLineItem, String> {
+        if !Path::new(scanned).is_file() {
+            return Err(format!("MISSING FILE: this file does not exist: {}", scanned));
+        }
+        if !Path::new(inbox).is_dir() {
+            return Err(format!("MISSING DIR: this dir does not exist: {}", inbox));
+        }
+        PdfVerification::new(scanned, &config.verify)?;
+
+        let ext = Path::new(scanned).extension().and_then(|ext| ext.to_str()).unwrap_or("pdf").to_lowercase();
+        let file_name = self.file_name(config, &ext)?;
+        let dest = format!("{}/{}", inbox, file_name);
+        let line_item = PdfLineItem::new(inbox, &dest)?;
+        if Path::new(&dest).exists() {
+            return Err(format!("FILE EXISTS: a receipt with this name is already in the inbox\n{}", dest));
+        }
+
+        // the scan and any sidecar that came with it
+        let mut transfers: Vec<(String, String)> = vec![(scanned.to_owned(), dest.clone())];
+        for format in [PdfSidecarFormat::Toml, PdfSidecarFormat::Json] {
+            let sidecar = PdfSidecar::path_for_receipt(scanned, &format);
+            if Path::new(&sidecar).is_file() {
+                transfers.push((sidecar, PdfSidecar::path_for_receipt(&dest, &format)));
+            }
+        }
+        for (from, to) in transfers {
+            transfer_file(&from, &to, move_file)?;
+        }
+
+        return Ok(line_item);
+    }
+
+}
+
+fn transfer_file(from: &str, to: &str, move_file: bool) -> Result<(), String> {
+    if move_file && fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // copying covers both plain copies and moves across filesystems
+    let result = fs::copy(from, to);
+    if result.is_err() {
+        println!("{:?}", result.err().unwrap()); // third-party error
+        return Err(format!("FILE COPY FAILURE: failed to copy {} to {}", from, to));
+    }
+    if move_file {
+        let result = fs::remove_file(from);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("FILE REMOVE FAILURE: copied {} but failed to remove the original", from));
+        }
+    }
+    return Ok(());
+}
+
+/// reads a value from stdin, a unique prefix of a known value completes to it
+/// and an empty line takes the default
+pub fn prompt(label: &str, known: &[String], default: Option<String>) -> Result<String, String> {
+    let stdin = io::stdin();
+    loop {
+        if !known.is_empty() {
+            print!("{} [{}]", label, known.join(", "));
+        } else {
+            print!("{}", label);
+        }
+        if default.is_some() {
+            print!(" (default {})", default.as_ref().unwrap());
+        }
+        print!(": ");
+        let _ = io::stdout().flush();
+
+        let mut input = String::new();
+        let read = stdin.lock().read_line(&mut input);
+        if read.is_err() || read.unwrap() == 0 {
+            return Err(format!("MISSING FIELD: no value was given for '{}'", label));
+        }
+        let input = input.trim().to_lowercase();
+        if input.is_empty() && default.is_some() {
+            return Ok(default.unwrap());
+        }
+        if input.is_empty() {
+            continue;
+        }
+        // a trailing '!' takes the value as typed rather than completing it to a known value,
+        // a '!' on its own is no answer at all
+        if input.ends_with("!") {
+            let typed = input.trim_end_matches("!").trim();
+            if typed.is_empty() {
+                continue;
+            }
+            return Ok(typed.to_owned());
+        }
+        if known.contains(&input) {
+            return Ok(input);
+        }
+        let matches: Vec<&String> = known.iter().filter(|value| value.starts_with(&input)).collect();
+        if matches.len() == 1 {
+            println!("{}: {}", label, matches[0]);
+            return Ok(matches[0].clone());
+        }
+        if matches.len() > 1 {
+            let matches: Vec<&str> = matches.iter().map(|value| value.as_str()).collect();
+            println!("'{}' matches {}, keep typing or end with '!' to skip completing it", input, matches.join(", "));
+            continue;
+        }
+        return Ok(input);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+/// the locations a split receipt is shared between, in the order tied remainder cents are handed out
+pub const SPLIT_LOCATIONS: [&str; 2] = ["utica", "southroads"];
+
+/// who takes the cents left over once every share is rounded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PdfRemainderPolicy {
+    /// the first location in order, utica
+    #[default]
+    First,
+    /// a different location for each split receipt, taking turns
+    Rotate,
+    /// the location with the largest share
+    Largest,
+    /// always the named location
+    House(String),
+}
+
+impl FromStr for PdfRemainderPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<PdfRemainderPolicy, String> {
+        if let Some(location) = policy.strip_prefix("house:") {
+            if !SPLIT_LOCATIONS.contains(&location) {
+                return Err(format!("INVALID REMAINDER POLICY: the house location must be one of {}\n{}", SPLIT_LOCATIONS.join(", "), policy));
+            }
+            return Ok(PdfRemainderPolicy::House(location.to_owned()));
+        }
+        return match policy {
+            "first" => Ok(PdfRemainderPolicy::First),
+            "rotate" => Ok(PdfRemainderPolicy::Rotate),
+            "largest" => Ok(PdfRemainderPolicy::Largest),
+            _ => Err(format!("INVALID REMAINDER POLICY: '{}' must be one of first, rotate, largest or house:<location>", policy)),
+        };
+    }
+}
+
+impl TryFrom<String> for PdfRemainderPolicy {
+    type Error = String;
+
+    fn try_from(policy: String) -> Result<PdfRemainderPolicy, String> {
+        return policy.parse();
+    }
+}
+
+impl fmt::Display for PdfRemainderPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            PdfRemainderPolicy::First => write!(f, "first"),
+            PdfRemainderPolicy::Rotate => write!(f, "rotate"),
+            PdfRemainderPolicy::Largest => write!(f, "largest"),
+            PdfRemainderPolicy::House(location) => write!(f, "house:{}", location),
+        };
+    }
+}
+
+impl From<PdfRemainderPolicy> for String {
+    fn from(policy: PdfRemainderPolicy) -> String {
+        return policy.to_string();
+    }
+}
+
+/// how a share that lands exactly on half a cent is rounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PdfRounding {
+    /// half a cent always rounds up
+    #[default]
+    #[serde(rename = "half-up")]
+    HalfUp,
+    /// half a cent rounds to the even cent
+    #[serde(rename = "bankers")]
+    Bankers,
+}
+
+impl PdfRounding {
+
+    pub fn strategy(&self) -> RoundingStrategy {
+        return match self {
+            PdfRounding::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            PdfRounding::Bankers => RoundingStrategy::MidpointNearestEven,
+        };
+    }
+
+}
+
+impl fmt::Display for PdfRounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            PdfRounding::HalfUp => write!(f, "half-up"),
+            PdfRounding::Bankers => write!(f, "bankers"),
+        };
+    }
+}
+
+/// the `[split]` section of the config, how split receipts are shared to the cent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PdfSplitOptions {
+    #[serde(default)]
+    pub remainder: PdfRemainderPolicy,
+    #[serde(default)]
+    pub rounding: PdfRounding,
+    /// the `[split.weights]` table, how much of a split receipt each location takes.
+    /// a location left out weighs 1, so without the table receipts are halved
+    #[serde(default)]
+    pub weights: BTreeMap<String, Decimal>,
+}
+
+impl PdfSplitOptions {
+
+    /// the weight of each of the split locations, in order
+    pub fn location_weights(&self) -> Result<Vec<Decimal>, String> {
+        for location in self.weights.keys() {
+            if !SPLIT_LOCATIONS.contains(&location.as_str()) {
+                return Err(format!("INVALID SPLIT WEIGHT: split weights are set per location, one of {}, not '{}'", SPLIT_LOCATIONS.join(", "), location));
+            }
+        }
+        let weights: Vec<Decimal> = SPLIT_LOCATIONS
+            .iter()
+            .map(|location| self.weights.get(*location).copied().unwrap_or(Decimal::ONE))
+            .collect();
+        return Ok(weights);
+    }
+
+}
+
+impl fmt::Display for PdfSplitOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "remainder {}, rounding {}", self.remainder, self.rounding)?;
+        if !self.weights.is_empty() {
+            let weights: Vec<String> = self.weights.iter().map(|(location, weight)| format!("{} {}", location, weight)).collect();
+            write!(f, ", weights {}", weights.join(", "))?;
+        }
+        return Ok(());
+    }
+}
+
+/// shares `amount` out by `weights` to the cent, the shares always add back up to `amount`.
+/// each party gets the whole cents of its exact share, then the cents left over go one each
+/// to the largest fractions left behind, ties going to the earlier party
+pub fn allocate(amount: Decimal, weights: &[Decimal]) -> Result<Vec<Decimal>, String> {
+    let (_, exact) = exact_cents(amount, weights)?;
+    let mut priority: Vec<usize> = (0..weights.len()).collect();
+    priority.sort_by(|a, b| exact[*b].fract().cmp(&exact[*a].fract()).then(a.cmp(b)));
+    return allocate_with(amount, weights, PdfRounding::HalfUp, &priority);
+}
+
+/// shares `amount` out by `weights` to the cent. shares whose fraction of a cent rounds up
+/// under `rounding` take a leftover cent first, largest fraction first, and any cents still
+/// left go to parties in `priority` order. `priority` also settles ties, and no share ends up
+/// a cent or more away from its exact value
+pub fn allocate_with(amount: Decimal, weights: &[Decimal], rounding: PdfRounding, priority: &[usize]) -> Result<Vec<Decimal>, String> {
+    let (cents, exact) = exact_cents(amount, weights)?;
+    let mut order: Vec<usize> = priority.iter().copied().filter(|i| *i < weights.len()).collect();
+    order.dedup();
+    if order.len() != weights.len() || (0..weights.len()).any(|i| !order.contains(&i)) {
+        return Err(format!("INVALID ALLOCATION: the priority {:?} must name every party once", priority));
+    }
+    // parties with no weight never take a cent
+    order.retain(|i| !weights[*i].is_zero());
+
+    let mut shares: Vec<Decimal> = exact.iter().map(|share| share.floor()).collect();
+    let remainder = cents - shares.iter().sum::<Decimal>();
+    if remainder.is_sign_negative() || remainder >= Decimal::from(weights.len()) {
+        return Err(format!("ALLOCATION ERROR: {} cents were left over sharing {} by {:?}", remainder, amount, weights));
+    }
+    let mut remainder = remainder.to_usize().unwrap_or(0);
+
+    // shares that round up take the leftover cents first
+    let mut rounding_up: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|i| exact[*i].round_dp_with_strategy(0, rounding.strategy()) > shares[*i])
+        .collect();
+    rounding_up.sort_by(|a, b| exact[*b].fract().cmp(&exact[*a].fract()));
+    for i in rounding_up.into_iter().chain(order.clone()) {
+        if remainder == 0 {
+            break;
+        }
+        // a share already on a whole cent is exact, only shares cut short take a cent
+        if shares[i] > exact[i].floor() || exact[i].fract().is_zero() {
+            continue;
+        }
+        shares[i] += Decimal::ONE;
+        remainder -= 1;
+    }
+
+    // working on the size of the amount so a refund shares out the same way as a charge
+    let negative = amount.is_sign_negative();
+    let shares: Vec<Decimal> = shares
+        .into_iter()
+        .map(|share| if negative && !share.is_zero() { -share / Decimal::from(100) } else { share / Decimal::from(100) })
+        .collect();
+    return Ok(shares);
+}
+
+// the amount in cents without its sign, and each party's exact share of it
+fn exact_cents(amount: Decimal, weights: &[Decimal]) -> Result<(Decimal, Vec<Decimal>), String> {
+    if weights.is_empty() {
+        return Err("INVALID ALLOCATION: an amount needs at least one party to be shared between".to_owned());
+    }
+    if weights.iter().any(|weight| weight.is_sign_negative() && !weight.is_zero()) {
+        return Err(format!("INVALID ALLOCATION: weights cannot be negative {:?}", weights));
+    }
+    let total_weight: Decimal = weights.iter().sum();
+    if total_weight.is_zero() {
+        return Err(format!("INVALID ALLOCATION: at least one weight must be above zero {:?}", weights));
+    }
+    let cents = amount * Decimal::from(100);
+    if !cents.fract().is_zero() {
+        return Err(format!("INVALID AMOUNT: {} has more than two decimals and cannot be shared to the cent", amount));
+    }
+    let cents = cents.abs();
+    let exact: Vec<Decimal> = weights.iter().map(|weight| cents * weight / total_weight).collect();
+    return Ok((cents, exact));
+}
+
+/// the order parties take leftover cents in under `policy`, `turn` counts the amounts shared
+/// out before this one so a rotating remainder moves on each time
+pub fn remainder_priority(policy: &PdfRemainderPolicy, parties: &[&str], weights: &[Decimal], turn: usize) -> Vec<usize> {
+    let count = parties.len();
+    let mut priority: Vec<usize> = (0..count).collect();
+    match policy {
+        PdfRemainderPolicy::First => {},
+        PdfRemainderPolicy::Rotate => {
+            priority = (0..count).map(|i| (i + turn) % count.max(1)).collect();
+        },
+        PdfRemainderPolicy::Largest => {
+            priority.sort_by(|a, b| weights[*b].cmp(&weights[*a]).then(a.cmp(b)));
+        },
+        PdfRemainderPolicy::House(house) => {
+            let house = parties.iter().position(|party| party == house);
+            if house.is_some() {
+                let house = house.unwrap();
+                priority.retain(|i| *i != house);
+                priority.insert(0, house);
+            }
+        },
+    }
+    return priority;
+}
+
+/// a split receipt's cost shared between the split locations by their weights
+pub fn split_cost(amount: Decimal, options: &PdfSplitOptions, turn: usize) -> Result<Vec<(String, Decimal)>, String> {
+    let weights = options.location_weights()?;
+    let priority = remainder_priority(&options.remainder, &SPLIT_LOCATIONS, &weights, turn);
+    let shares = allocate_with(amount, &weights, options.rounding, &priority)?;
+    return Ok(SPLIT_LOCATIONS.iter().map(|location| location.to_string()).zip(shares).collect());
+}
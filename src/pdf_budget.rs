@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use genpdf::{elements, style, Alignment, Element};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::pdf_allocate::SPLIT_LOCATIONS;
+use crate::pdf_config::PdfConfig;
+use crate::pdf_csv::write_csv;
+use crate::pdf_invoice::{location_shares, new_document, PdfExpenseCategory, PdfLineItem, PdfReadOptions, PdfSkippedFile};
+use crate::pdf_period::{PdfPeriod, PdfPeriodLength};
+
+/// the `[budget.<location>]` sections of the config, monthly budgets by location then category
+pub type PdfBudgets = BTreeMap<String, BTreeMap<String, Decimal>>;
+
+/// spending against the budget for one category, or a whole location
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfBudgetLine {
+    pub name: String,
+    pub budget: Option<Decimal>,
+    pub spent: Decimal,
+    pub remaining: Option<Decimal>,
+    pub percent_used: Option<Decimal>,
+    pub over_budget: bool,
+}
+
+impl PdfBudgetLine {
+
+    /// categories with no budget leave `budget`, `remaining` and `percent_used` empty
+    pub fn new(name: &str, budget: Option<Decimal>, mut spent: Decimal) -> PdfBudgetLine {
+        let budget = budget.map(|mut budget| { budget.rescale(2); budget });
+        spent.rescale(2);
+        let percent_used = budget.filter(|budget| !budget.is_zero()).map(|budget| {
+            let mut percent = (spent * Decimal::ONE_HUNDRED / budget).round_dp(1);
+            percent.rescale(1);
+            percent
+        });
+        return PdfBudgetLine {
+            name: name.to_owned(),
+            budget: budget,
+            spent: spent,
+            remaining: budget.map(|budget| budget - spent),
+            percent_used: percent_used,
+            over_budget: budget.is_some() && spent > budget.unwrap(),
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        if self.budget.is_none() {
+            return format!("{}: spent {:.2} with no budget", self.name, self.spent);
+        }
+        let percent = self.percent_used.map(|percent| format!(" ({}% used)", percent)).unwrap_or_default();
+        let over = if self.over_budget { " OVER BUDGET" } else { "" };
+        return format!("{}: spent {:.2} of {:.2}, {:.2} remaining{}{}", self.name, self.spent, self.budget.unwrap(), self.remaining.unwrap(), percent, over);
+    }
+
+}
+
+/// one location's categories against its budget
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfBudgetLocation {
+    pub location: String,
+    pub total: PdfBudgetLine,
+    pub categories: Vec<PdfBudgetLine>,
+}
+
+/// what every location spent in a period against its budgets
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfBudgetReport {
+    pub period: String,
+    pub months: i64,
+    pub locations: Vec<PdfBudgetLocation>,
+    pub skipped: Vec<PdfSkippedFile>,
+}
+
+impl PdfBudgetReport {
+
+    /// the monthly budgets are scaled up to the length of the period, and split receipts are
+    /// shared out between locations the same way `sort` shares them. without a period the
+    /// month of the latest receipt is used
+    pub fn new(dir: &str, period: Option<PdfPeriod>, read: &PdfReadOptions, config: &PdfConfig) -> Result<PdfBudgetReport, String> {
+        for (location, categories) in &config.budget {
+            if !SPLIT_LOCATIONS.contains(&location.to_lowercase().as_str()) {
+                return Err(format!("INVALID BUDGET: budgets are set per location, one of {}, not '{}'", SPLIT_LOCATIONS.join(", "), location));
+            }
+            for (category, budget) in categories {
+                if budget.is_sign_negative() {
+                    return Err(format!("INVALID BUDGET: the {} budget for {} can't be negative", category, location));
+                }
+            }
+        }
+
+        let (line_items, skipped) = PdfLineItem::new_from_dir(dir, read)?;
+        let period = match period {
+            Some(period) => period,
+            None => {
+                let dates: Vec<&str> = line_items.iter().map(|item| item.date.as_str()).collect();
+                let latest = PdfPeriod::latest(&dates, PdfPeriodLength::Month);
+                if latest.is_none() {
+                    return Err(format!("EMPTY DIR: {} has no receipts to pick a period from, pass --period", dir));
+                }
+                latest.unwrap()
+            },
+        };
+
+        // each location's share of the receipts in the period
+        let mut spending: BTreeMap<String, Vec<PdfLineItem>> = BTreeMap::new();
+        let all_shares = location_shares(&line_items, &config.split)?;
+        for (item, shares) in line_items.iter().zip(all_shares) {
+            if !period.contains(&item.date) {
+                continue;
+            }
+            for (location, cost) in shares {
+                let mut share = item.clone();
+                share.cost = cost;
+                share.category = share.category.to_lowercase();
+                spending.entry(location).or_default().push(share);
+            }
+        }
+
+        let mut location_names: Vec<String> = config.budget.keys().map(|location| location.to_lowercase()).collect();
+        location_names.extend(spending.keys().cloned());
+        location_names.sort();
+        location_names.dedup();
+
+        let months = Decimal::from(period.months());
+        let mut locations: Vec<PdfBudgetLocation> = vec![];
+        for location in location_names {
+            let budgets: BTreeMap<String, Decimal> = config.budget
+                .iter()
+                .filter(|(name, _)| name.to_lowercase() == location)
+                .flat_map(|(_, categories)| categories.iter().map(|(category, budget)| (category.to_lowercase(), *budget * months)))
+                .collect();
+            let expense_categories = PdfExpenseCategory::new_from_line_items(spending.remove(&location).unwrap_or_default());
+
+            let mut category_names: Vec<String> = budgets.keys().cloned().collect();
+            category_names.extend(expense_categories.iter().map(|category| category.name.clone()));
+            category_names.sort();
+            category_names.dedup();
+
+            let mut categories: Vec<PdfBudgetLine> = vec![];
+            for category in category_names {
+                let spent = expense_categories.iter().find(|expense| expense.name == category).map(|expense| expense.total_cost).unwrap_or(Decimal::ZERO);
+                categories.push(PdfBudgetLine::new(&category, budgets.get(&category).copied(), spent));
+            }
+            let budget: Decimal = budgets.values().sum();
+            let spent: Decimal = categories.iter().map(|category| category.spent).sum();
+            locations.push(PdfBudgetLocation {
+                total: PdfBudgetLine::new("total", if budgets.is_empty() { None } else { Some(budget) }, spent),
+                location: location,
+                categories: categories,
+            });
+        }
+
+        let report = PdfBudgetReport {
+            period: period.to_string(),
+            months: period.months(),
+            locations: locations,
+            skipped: skipped,
+        };
+        return Ok(report);
+    }
+
+    /// every category that went over, as `location: category`
+    pub fn over_budget(&self) -> Vec<String> {
+        return self.locations
+            .iter()
+            .flat_map(|location| location.categories.iter().filter(|category| category.over_budget).map(move |category| format!("{}: {}", location.location, category.name)))
+            .collect();
+    }
+
+    /// one row per category, then a `total` row per location
+    pub fn write_csv(&self, path: &str) -> Result<(), String> {
+        let header = ["period", "location", "category", "budget", "spent", "remaining", "percent_used", "over_budget"];
+        let mut rows: Vec<Vec<String>> = vec![];
+        for location in &self.locations {
+            for line in location.categories.iter().chain([&location.total]) {
+                rows.push(vec![
+                    self.period.clone(),
+                    location.location.clone(),
+                    line.name.clone(),
+                    line.budget.map(|budget| format!("{:.2}", budget)).unwrap_or_default(),
+                    format!("{:.2}", line.spent),
+                    line.remaining.map(|remaining| format!("{:.2}", remaining)).unwrap_or_default(),
+                    line.percent_used.map(|percent| percent.to_string()).unwrap_or_default(),
+                    line.over_budget.to_string(),
+                ]);
+            }
+        }
+        return write_csv(path, &header, &rows);
+    }
+
+    pub fn write_json(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self);
+        if json.is_err() {
+            println!("{:?}", json.err().unwrap()); // third-party error
+            return Err("JSON FAILURE: failed to serialize the budget report".to_owned());
+        }
+        let result = fs::write(path, json.unwrap() + "\n");
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("FILE WRITE FAILURE: failed to write {}", path));
+        }
+        return Ok(());
+    }
+
+    pub fn write_pdf(&self, path: &str) -> Result<(), String> {
+        let months = if self.months == 1 { "1 month".to_owned() } else { format!("{} months", self.months) };
+        let title = format!("budget: {} ({})", self.period, months);
+        let mut doc = new_document(&title);
+        doc.push(elements::Paragraph::new(&title).aligned(Alignment::Left).styled(style::Style::new().bold().with_font_size(20)));
+        doc.push(elements::Break::new(1));
+
+        for location in &self.locations {
+            let header = format!("{} => {}", location.location, location.total.describe().trim_start_matches("total: "));
+            doc.push(elements::Paragraph::new(header).styled(style::Style::new().bold().with_font_size(16)));
+            for category in &location.categories {
+                let mut line_style = style::Style::new();
+                if category.over_budget {
+                    line_style = line_style.bold().with_color(style::Color::Rgb(200, 0, 0));
+                }
+                doc.push(elements::Paragraph::new(category.describe()).styled(line_style));
+            }
+            doc.push(elements::Break::new(1));
+        }
+
+        let output_file = doc.render_to_file(path);
+        if output_file.is_err() {
+            println!("{:?}", output_file.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", path));
+        }
+        return Ok(());
+    }
+
+}
@@ -0,0 +1,191 @@
+use genpdf::error::Error;
+use genpdf::render::Area;
+use genpdf::style::{Color, Style};
+use genpdf::{Context, Element, Position, RenderResult, Size};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+// every size below is in mm
+const TITLE_HEIGHT: f64 = 8.0;
+const ROW_HEIGHT: f64 = 7.0;
+const BAR_HEIGHT: f64 = 4.5;
+const LABEL_WIDTH: f64 = 45.0;
+const VALUE_WIDTH: f64 = 30.0;
+const PLOT_HEIGHT: f64 = 55.0;
+const AXIS_WIDTH: f64 = 22.0;
+const AXIS_LABEL_HEIGHT: f64 = 7.0;
+const MONTH_LABEL_WIDTH: f64 = 10.0;
+const MAX_LABEL_CHARS: usize = 22;
+// genpdf only strokes lines, so bars are filled with lines packed closer than their own width
+const FILL_STEP: f64 = 0.25;
+
+const PALETTE: [Color; 6] = [
+    Color::Rgb(52, 101, 164),
+    Color::Rgb(245, 121, 0),
+    Color::Rgb(78, 154, 6),
+    Color::Rgb(204, 0, 0),
+    Color::Rgb(117, 80, 123),
+    Color::Rgb(193, 125, 17),
+];
+
+fn to_f64(value: Decimal) -> f64 {
+    return value.to_f64().unwrap_or(0.0);
+}
+
+fn short_label(label: &str) -> String {
+    if label.chars().count() <= MAX_LABEL_CHARS {
+        return label.to_owned();
+    }
+    return label.chars().take(MAX_LABEL_CHARS - 1).collect::<String>() + "~";
+}
+
+// a solid rectangle, with its top left corner at (x, y)
+fn fill_rect(area: &Area<'_>, x: f64, y: f64, width: f64, height: f64, color: Color) {
+    if width <= 0.0 {
+        return;
+    }
+    let mut line_y = y;
+    while line_y <= y + height {
+        area.draw_line(vec![Position::new(x, line_y), Position::new(x + width, line_y)], Style::new().with_color(color));
+        line_y += FILL_STEP;
+    }
+}
+
+/// a horizontal bar chart with a labelled bar per total, running onto the next page when it has to
+pub struct PdfBarChart {
+    pub title: String,
+    pub bars: Vec<(String, Decimal)>,
+    next: usize,
+}
+
+impl PdfBarChart {
+    pub fn new(title: &str, bars: Vec<(String, Decimal)>) -> PdfBarChart {
+        return PdfBarChart {
+            title: title.to_owned(),
+            bars: bars,
+            next: 0,
+        };
+    }
+}
+
+impl Element for PdfBarChart {
+    fn render(&mut self, context: &Context, area: Area<'_>, style: Style) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let width: f64 = area.size().width.into();
+        let height: f64 = area.size().height.into();
+        let first = self.next == 0;
+        let title_height = if first { TITLE_HEIGHT } else { 0.0 };
+        if height < title_height + ROW_HEIGHT {
+            result.has_more = true;
+            return Ok(result);
+        }
+
+        let mut y = 0.0;
+        if first {
+            area.print_str(&context.font_cache, Position::new(0, 0), style.bold().with_font_size(14), &self.title)?;
+            y += TITLE_HEIGHT;
+            if self.bars.is_empty() {
+                area.print_str(&context.font_cache, Position::new(0, y), style.with_font_size(10), "nothing to chart")?;
+                y += ROW_HEIGHT;
+            }
+        }
+
+        // bars are scaled to the largest total, refunds run the same way as spending
+        let largest = self.bars.iter().map(|(_, value)| value.abs()).max().unwrap_or(Decimal::ZERO);
+        let bar_width = (width - LABEL_WIDTH - VALUE_WIDTH).max(0.0);
+        let label_style = style.with_font_size(10);
+        while self.next < self.bars.len() {
+            if y + ROW_HEIGHT > height {
+                result.has_more = true;
+                break;
+            }
+            let (label, value) = &self.bars[self.next];
+            let length = if largest.is_zero() { 0.0 } else { bar_width * to_f64(value.abs() / largest) };
+            area.print_str(&context.font_cache, Position::new(0, y), label_style, short_label(label))?;
+            fill_rect(&area, LABEL_WIDTH, y + (ROW_HEIGHT - BAR_HEIGHT) / 2.0, length, BAR_HEIGHT, PALETTE[self.next % PALETTE.len()]);
+            area.print_str(&context.font_cache, Position::new(LABEL_WIDTH + length + 2.0, y), label_style, format!("{:.2}", value))?;
+            y += ROW_HEIGHT;
+            self.next += 1;
+        }
+
+        result.size = Size::new(width, y);
+        return Ok(result);
+    }
+}
+
+/// a line chart of totals in order, e.g. month by month
+pub struct PdfLineChart {
+    pub title: String,
+    pub points: Vec<(String, Decimal)>,
+}
+
+impl PdfLineChart {
+    pub fn new(title: &str, points: Vec<(String, Decimal)>) -> PdfLineChart {
+        return PdfLineChart {
+            title: title.to_owned(),
+            points: points,
+        };
+    }
+}
+
+impl Element for PdfLineChart {
+    fn render(&mut self, context: &Context, area: Area<'_>, style: Style) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let width: f64 = area.size().width.into();
+        let height: f64 = area.size().height.into();
+        let needed = TITLE_HEIGHT + PLOT_HEIGHT + AXIS_LABEL_HEIGHT;
+        if height < needed {
+            result.has_more = true;
+            return Ok(result);
+        }
+        area.print_str(&context.font_cache, Position::new(0, 0), style.bold().with_font_size(14), &self.title)?;
+        if self.points.is_empty() {
+            area.print_str(&context.font_cache, Position::new(0, TITLE_HEIGHT), style.with_font_size(10), "nothing to chart")?;
+            result.size = Size::new(width, TITLE_HEIGHT + ROW_HEIGHT);
+            return Ok(result);
+        }
+
+        // the axes, always taking in zero
+        let label_style = style.with_font_size(8);
+        let top = TITLE_HEIGHT;
+        let bottom = TITLE_HEIGHT + PLOT_HEIGHT;
+        let left = AXIS_WIDTH;
+        let right = width;
+        let highest = self.points.iter().map(|(_, value)| *value).max().unwrap_or(Decimal::ZERO).max(Decimal::ZERO);
+        let lowest = self.points.iter().map(|(_, value)| *value).min().unwrap_or(Decimal::ZERO).min(Decimal::ZERO);
+        let range = if highest == lowest { 1.0 } else { to_f64(highest - lowest) };
+        let y_of = |value: Decimal| bottom - PLOT_HEIGHT * to_f64(value - lowest) / range;
+        area.draw_line(vec![Position::new(left, top), Position::new(left, bottom), Position::new(right, bottom)], Style::new());
+        if !lowest.is_zero() {
+            area.draw_line(vec![Position::new(left, y_of(Decimal::ZERO)), Position::new(right, y_of(Decimal::ZERO))], Style::new().with_color(Color::Rgb(160, 160, 160)));
+        }
+        let line_height: f64 = label_style.line_height(&context.font_cache).into();
+        area.print_str(&context.font_cache, Position::new(0, top - line_height / 2.0), label_style, format!("{:.2}", highest))?;
+        area.print_str(&context.font_cache, Position::new(0, bottom - line_height / 2.0), label_style, format!("{:.2}", lowest))?;
+
+        // the line itself, with the month labels thinned out when they would overlap
+        let step = (right - left) / self.points.len() as f64;
+        let label_every = (MONTH_LABEL_WIDTH / step).ceil().max(1.0) as usize;
+        let positions: Vec<(f64, f64)> = self.points
+            .iter()
+            .enumerate()
+            .map(|(i, (_, value))| (left + step * (i as f64 + 0.5), y_of(*value)))
+            .collect();
+        if positions.len() > 1 {
+            area.draw_line(positions.iter().map(|(x, y)| Position::new(*x, *y)).collect(), Style::new().with_color(PALETTE[0]));
+        }
+        for (i, ((label, value), (x, y))) in self.points.iter().zip(positions).enumerate() {
+            fill_rect(&area, x - 0.8, y - 0.8, 1.6, 1.6, PALETTE[0]);
+            if i % label_every != 0 {
+                continue;
+            }
+            area.print_str(&context.font_cache, Position::new(x - MONTH_LABEL_WIDTH / 2.0, bottom + 1.0), label_style, label)?;
+            if label_every == 1 {
+                area.print_str(&context.font_cache, Position::new(x - MONTH_LABEL_WIDTH / 2.0, y - line_height - 1.0), label_style, format!("{:.0}", value))?;
+            }
+        }
+
+        result.size = Size::new(width, needed);
+        return Ok(result);
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::Path;
+
+use rust_decimal::Decimal;
+
+use crate::pdf_csv::write_csv;
+use crate::pdf_ignore::PdfIgnore;
+use crate::pdf_invoice::{is_receipt_path, receipt_for_sidecar, PdfLineItem};
+use crate::pdf_sidecar::PdfSidecar;
+use crate::pdf_sort_manifest::PdfSortManifest;
+use crate::pdf_sort_summary::PdfSortSummary;
+use crate::pdf_text::PdfText;
+use crate::pdf_verify::{PdfVerification, PdfVerifyOptions};
+
+/// a problem found with one file
+#[derive(Debug, Clone)]
+pub struct PdfCheckIssue {
+    pub path: String,
+    pub message: String,
+}
+
+/// a receipt whose text does not back up the amount or date in its name
+#[derive(Debug, Clone)]
+pub struct PdfAuditMismatch {
+    pub path: String,
+    pub date: String,
+    pub cost: Decimal,
+    pub has_text: bool,
+    pub date_found: bool,
+    pub amount_found: bool,
+}
+
+impl PdfAuditMismatch {
+    pub fn reason(&self) -> String {
+        if !self.has_text {
+            return "no text layer to audit".to_owned();
+        }
+        let mut missing: Vec<&str> = vec![];
+        if !self.amount_found {
+            missing.push("amount");
+        }
+        if !self.date_found {
+            missing.push("date");
+        }
+        return format!("{} not found in document", missing.join(" and "));
+    }
+}
+
+/// everything `finli check` found in a dir
+#[derive(Debug)]
+pub struct PdfCheckReport {
+    pub line_items: Vec<PdfLineItem>,
+    pub verifications: Vec<PdfVerification>,
+    pub issues: Vec<PdfCheckIssue>,
+    pub mismatches: Vec<PdfAuditMismatch>,
+}
+
+impl PdfCheckReport {
+
+    /// validates every file in the dir, collecting problems instead of stopping at the first
+    pub fn new(dir: &str, audit: bool, verify: &PdfVerifyOptions) -> Result<PdfCheckReport, String> {
+        let dir_path = Path::new(dir);
+        if !dir_path.is_dir() {
+            return Err(format!("MISSING DIR: this dir does not exist: {:?}", dir_path));
+        }
+        let entries = fs::read_dir(dir_path);
+        if entries.is_err() {
+            println!("{:?}", entries.err().unwrap()); // third-party error
+            return Err(format!("READ DIR FAILURE: failed to read the contents of {}", dir));
+        }
+        let ignore = PdfIgnore::new_from_dir(dir)?;
+        let mut paths: Vec<String> = entries
+            .unwrap()
+            .flatten()
+            .filter(|entry| !ignore.is_ignored(Path::new(&entry.file_name()), entry.path().is_dir()))
+            .map(|entry| format!("{}/{}", dir, entry.file_name().to_string_lossy()))
+            .collect();
+        paths.sort();
+
+        let mut report = PdfCheckReport {
+            line_items: vec![],
+            verifications: vec![],
+            issues: vec![],
+            mismatches: vec![],
+        };
+        for path in paths {
+            let file_path = Path::new(&path);
+            if file_path.is_dir() {
+                report.issue(&path, "INVALID DIR CONTENTS: the provided file path must not contain any subdirectories");
+                continue;
+            }
+            if PdfSortSummary::is_summary_path(file_path) || PdfSortManifest::is_manifest_path(file_path) {
+                continue;
+            }
+            if PdfSidecar::is_sidecar_path(file_path) {
+                if receipt_for_sidecar(file_path).is_none() {
+                    report.issue(&path, "ORPHAN SIDECAR: sidecar file has no matching receipt");
+                }
+                continue;
+            }
+            if !is_receipt_path(file_path) {
+                report.issue(&path, "INVALID FILE EXTENSION: the dir must contain only .pdf, .jpg, .jpeg or .png files");
+                continue;
+            }
+            let line_item = PdfLineItem::new(dir, &path);
+            if line_item.is_err() {
+                report.issue(&path, &line_item.err().unwrap());
+                continue;
+            }
+            let mut line_item = line_item.unwrap();
+            let sidecar = PdfSidecar::new_for_receipt(&path);
+            if sidecar.is_err() {
+                report.issue(&path, &sidecar.err().unwrap());
+                continue;
+            }
+            if let Some(sidecar) = sidecar.unwrap() {
+                let err = sidecar.apply(&mut line_item);
+                if err.is_some() {
+                    report.issue(&path, &err.unwrap());
+                    continue;
+                }
+            }
+
+            // a well named file still has to open
+            let verification = PdfVerification::new(&path, verify);
+            if verification.is_err() {
+                report.issue(&path, &verification.err().unwrap());
+                continue;
+            }
+            report.verifications.push(verification.unwrap());
+            report.line_items.push(line_item);
+        }
+
+        if audit {
+            report.audit();
+        }
+        return Ok(report);
+    }
+
+    fn issue(&mut self, path: &str, message: &str) {
+        self.issues.push(PdfCheckIssue {
+            path: path.to_owned(),
+            message: message.to_owned(),
+        });
+    }
+
+    // looks for each receipt's claimed cost and date in its own text
+    fn audit(&mut self) {
+        for item in &self.line_items {
+            let text = PdfText::new(&item.path);
+            if text.is_err() {
+                self.issues.push(PdfCheckIssue {
+                    path: item.path.clone(),
+                    message: text.err().unwrap(),
+                });
+                continue;
+            }
+            let text = text.unwrap();
+            let has_text = text.has_text();
+            let amount_found = has_text && text.contains_amount(item.cost);
+            let date_found = has_text && text.contains_date(&item.date);
+            if amount_found && date_found {
+                continue;
+            }
+            self.mismatches.push(PdfAuditMismatch {
+                path: item.path.clone(),
+                date: item.date.clone(),
+                cost: item.cost,
+                has_text: has_text,
+                date_found: date_found,
+                amount_found: amount_found,
+            });
+        }
+    }
+
+    pub fn write_mismatch_csv(&self, path: &str) -> Result<(), String> {
+        let header = ["path", "date", "cost", "has_text", "date_found", "amount_found", "reason"];
+        let rows: Vec<Vec<String>> = self
+            .mismatches
+            .iter()
+            .map(|mismatch| {
+                vec![
+                    mismatch.path.clone(),
+                    mismatch.date.clone(),
+                    mismatch.cost.to_string(),
+                    mismatch.has_text.to_string(),
+                    mismatch.date_found.to_string(),
+                    mismatch.amount_found.to_string(),
+                    mismatch.reason(),
+                ]
+            })
+            .collect();
+        return write_csv(path, &header, &rows);
+    }
+
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::pdf_allocate::PdfSplitOptions;
+use crate::pdf_budget::PdfBudgets;
+use crate::pdf_settle::PdfSettleConfig;
+use crate::pdf_verify::PdfVerifyOptions;
+
+pub const DEFAULT_CONFIG_FILE: &str = "finli.toml";
+
+/// the contents of `finli.toml`, every section is optional
+#[derive(Debug, Default, Deserialize)]
+pub struct PdfConfig {
+    #[serde(default)]
+    pub aliases: PdfAliases,
+    #[serde(default)]
+    pub suggest: Vec<PdfSuggestRule>,
+    #[serde(default)]
+    pub verify: PdfVerifyOptions,
+    #[serde(default)]
+    pub split: PdfSplitOptions,
+    #[serde(default)]
+    pub settle: PdfSettleConfig,
+    #[serde(default)]
+    pub budget: PdfBudgets,
+}
+
+/// maps of alias => canonical value, keys are matched case-insensitively
+#[derive(Debug, Default, Deserialize)]
+pub struct PdfAliases {
+    #[serde(default)]
+    pub vendor: BTreeMap<String, String>,
+    #[serde(default)]
+    pub category: BTreeMap<String, String>,
+    #[serde(default)]
+    pub location: BTreeMap<String, String>,
+}
+
+/// a per-vendor rule for reading receipt text, the first capture group of
+/// `total` and `date` holds the value
+#[derive(Debug, Clone, Deserialize)]
+pub struct PdfSuggestRule {
+    pub vendor: String,
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub total: Option<String>,
+    pub date: Option<String>,
+}
+
+impl PdfConfig {
+
+    pub fn new_from_file(path: &str) -> Result<PdfConfig, String> {
+        let contents = fs::read_to_string(path);
+        if contents.is_err() {
+            println!("{:?}", contents.err().unwrap()); // third-party error
+            return Err(format!("CONFIG READ FAILURE: failed to read config file {}", path));
+        }
+        let config = toml::from_str::<PdfConfig>(&contents.unwrap());
+        if config.is_err() {
+            println!("{}", config.err().unwrap()); // third-party error
+            return Err(format!("INVALID CONFIG: failed to parse config file {}", path));
+        }
+        return Ok(config.unwrap());
+    }
+
+    /// an explicit path must exist, otherwise ./finli.toml is used when present
+    pub fn load(path: Option<&str>) -> Result<PdfConfig, String> {
+        if path.is_some() {
+            return PdfConfig::new_from_file(path.unwrap());
+        }
+        if Path::new(DEFAULT_CONFIG_FILE).is_file() {
+            return PdfConfig::new_from_file(DEFAULT_CONFIG_FILE);
+        }
+        return Ok(PdfConfig::default());
+    }
+
+}
+
+impl PdfAliases {
+
+    pub fn vendor(&self, value: &str) -> String {
+        return PdfAliases::resolve(&self.vendor, value);
+    }
+
+    pub fn category(&self, value: &str) -> String {
+        return PdfAliases::resolve(&self.category, value);
+    }
+
+    pub fn location(&self, value: &str) -> String {
+        return PdfAliases::resolve(&self.location, value);
+    }
+
+    fn resolve(aliases: &BTreeMap<String, String>, value: &str) -> String {
+        for (alias, canonical) in aliases {
+            if alias.to_lowercase() == value.to_lowercase() {
+                return canonical.to_lowercase();
+            }
+        }
+        return value.to_owned();
+    }
+
+}
@@ -0,0 +1,28 @@
+use std::fs;
+
+/// quotes a field when it holds a separator, quote or newline
+pub fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        return format!("\"{}\"", field.replace("\"", "\"\""));
+    }
+    return field.to_owned();
+}
+
+pub fn csv_row(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    return fields.join(",");
+}
+
+pub fn write_csv(path: &str, header: &[&str], rows: &[Vec<String>]) -> Result<(), String> {
+    let header: Vec<String> = header.iter().map(|field| field.to_string()).collect();
+    let mut contents = csv_row(&header) + "\n";
+    for row in rows {
+        contents += &(csv_row(row) + "\n");
+    }
+    let result = fs::write(path, contents);
+    if result.is_err() {
+        println!("{:?}", result.err().unwrap()); // third-party error
+        return Err(format!("CSV WRITE FAILURE: failed to write {}", path));
+    }
+    return Ok(());
+}
@@ -0,0 +1,180 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::pdf_hash::hash_file;
+use crate::pdf_invoice::PdfLineItem;
+
+pub const DEFAULT_DUPLICATE_DAYS: i64 = 3;
+
+/// how close two receipts have to be to count as the same purchase, and whether to drop extra copies
+#[derive(Debug, Clone)]
+pub struct PdfDuplicateOptions {
+    pub days: i64,
+    pub exclude: bool,
+}
+
+impl Default for PdfDuplicateOptions {
+    fn default() -> PdfDuplicateOptions {
+        return PdfDuplicateOptions {
+            days: DEFAULT_DUPLICATE_DAYS,
+            exclude: false,
+        };
+    }
+}
+
+/// exact duplicates are the same file, likely ones only look like the same purchase
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfDuplicateKind {
+    Exact,
+    Likely,
+}
+
+/// receipts that look like scans of the same purchase, in path order
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfDuplicateGroup {
+    pub kind: PdfDuplicateKind,
+    pub reason: String,
+    pub paths: Vec<String>,
+    pub cost: Decimal,
+}
+
+/// every group of duplicates found in a set of receipts
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PdfDuplicateReport {
+    pub groups: Vec<PdfDuplicateGroup>,
+}
+
+impl PdfDuplicateReport {
+
+    /// exact duplicates share their content, likely duplicates share a vendor
+    /// and amount with dates no more than `days` apart
+    pub fn new(line_items: &[PdfLineItem], days: i64) -> Result<PdfDuplicateReport, String> {
+        let mut items: Vec<&PdfLineItem> = line_items.iter().collect();
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut hashes: Vec<String> = vec![];
+        for item in &items {
+            hashes.push(hash_file(&item.path)?);
+        }
+
+        let mut report = PdfDuplicateReport::default();
+
+        // grouping by content
+        let mut grouped: Vec<bool> = vec![false; items.len()];
+        for i in 0..items.len() {
+            if grouped[i] {
+                continue;
+            }
+            let matches: Vec<usize> = (i..items.len()).filter(|j| hashes[*j] == hashes[i]).collect();
+            if matches.len() < 2 {
+                continue;
+            }
+            for j in &matches {
+                grouped[*j] = true;
+            }
+            report.groups.push(PdfDuplicateGroup {
+                kind: PdfDuplicateKind::Exact,
+                reason: "same file contents".to_owned(),
+                paths: matches.iter().map(|j| items[*j].path.clone()).collect(),
+                cost: items[i].cost,
+            });
+        }
+
+        // linking different files that look like the same purchase, a chain of
+        // close dates ends up in one group
+        let mut group_of: Vec<usize> = (0..items.len()).collect();
+        for i in 0..items.len() {
+            for j in (i + 1)..items.len() {
+                if hashes[i] == hashes[j] || !PdfDuplicateReport::likely(items[i], items[j], days) {
+                    continue;
+                }
+                let (from, to) = (find(&group_of, j), find(&group_of, i));
+                group_of[from] = to;
+            }
+        }
+        for i in 0..items.len() {
+            if find(&group_of, i) != i {
+                continue;
+            }
+            let members: Vec<usize> = (0..items.len()).filter(|j| find(&group_of, *j) == i).collect();
+            if members.len() < 2 {
+                continue;
+            }
+            report.groups.push(PdfDuplicateGroup {
+                kind: PdfDuplicateKind::Likely,
+                reason: format!("same vendor and amount within {} days", days),
+                paths: members.iter().map(|j| items[*j].path.clone()).collect(),
+                cost: items[i].cost,
+            });
+        }
+
+        return Ok(report);
+    }
+
+    fn likely(a: &PdfLineItem, b: &PdfLineItem, days: i64) -> bool {
+        if a.vendor.to_lowercase() != b.vendor.to_lowercase() || a.cost != b.cost {
+            return false;
+        }
+        let (a_day, b_day) = (day_number(&a.date), day_number(&b.date));
+        if a_day.is_none() || b_day.is_none() {
+            return false;
+        }
+        return (a_day.unwrap() - b_day.unwrap()).abs() <= days;
+    }
+
+    /// the extra copies of exact duplicates, the first of each group is kept
+    pub fn confirmed(&self) -> Vec<String> {
+        let mut confirmed: Vec<String> = vec![];
+        for group in &self.groups {
+            if group.kind != PdfDuplicateKind::Exact {
+                continue;
+            }
+            confirmed.extend(group.paths.iter().skip(1).cloned());
+        }
+        return confirmed;
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for group in &self.groups {
+            let kind = match group.kind {
+                PdfDuplicateKind::Exact => "exact duplicate",
+                PdfDuplicateKind::Likely => "likely duplicate",
+            };
+            text += &format!("{} ({}, {}):\n", kind, group.reason, group.cost);
+            for (i, path) in group.paths.iter().enumerate() {
+                if group.kind == PdfDuplicateKind::Exact && i > 0 {
+                    text += &format!("  {} (confirmed duplicate)\n", path);
+                } else {
+                    text += &format!("  {}\n", path);
+                }
+            }
+        }
+        return text;
+    }
+
+}
+
+fn find(group_of: &[usize], mut i: usize) -> usize {
+    while group_of[i] != i {
+        i = group_of[i];
+    }
+    return i;
+}
+
+/// days since 2000-01-01 for an MMDDYY date
+pub fn day_number(date: &str) -> Option<i64> {
+    if date.len() != 6 {
+        return None;
+    }
+    let month: i64 = date[0..2].parse().ok()?;
+    let day: i64 = date[2..4].parse().ok()?;
+    let year: i64 = 2000 + date[4..6].parse::<i64>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // counting from march so the leap day falls at the end of the year
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let days = 365 * year + year / 4 - year / 100 + year / 400 + (153 * month + 2) / 5 + day - 1;
+    return Some(days - 730425);
+}
@@ -0,0 +1,30 @@
+use std::fs::File;
+use std::io::Read;
+
+use sha2::{Digest, Sha256};
+
+/// the sha256 of a file's contents as lowercase hex
+pub fn hash_file(path: &str) -> Result<String, String> {
+    let file = File::open(path);
+    if file.is_err() {
+        println!("{:?}", file.err().unwrap()); // third-party error
+        return Err(format!("HASH FAILURE: failed to open {} for hashing", path));
+    }
+    let mut file = file.unwrap();
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer);
+        if read.is_err() {
+            println!("{:?}", read.err().unwrap()); // third-party error
+            return Err(format!("HASH FAILURE: failed to read {} for hashing", path));
+        }
+        let read = read.unwrap();
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    let hash = hasher.finalize();
+    return Ok(hash.iter().map(|byte| format!("{:02x}", byte)).collect());
+}
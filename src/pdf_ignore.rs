@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::Path;
+
+use glob::Pattern;
+
+pub const IGNORE_FILE: &str = ".finliignore";
+
+/// one line of a `.finliignore`, a trailing '/' only matches dirs
+#[derive(Debug, Clone)]
+pub struct PdfIgnoreRule {
+    pub pattern: Pattern,
+    pub dir_only: bool,
+    pub anchored: bool,
+}
+
+/// glob patterns for files and dirs that reading a receipt dir should pass over
+#[derive(Debug, Clone, Default)]
+pub struct PdfIgnore {
+    pub rules: Vec<PdfIgnoreRule>,
+}
+
+impl PdfIgnore {
+
+    /// reads `<dir>/.finliignore`, a dir without one ignores nothing
+    pub fn new_from_dir(dir: &str) -> Result<PdfIgnore, String> {
+        let path = Path::new(dir).join(IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(PdfIgnore::default());
+        }
+        let contents = fs::read_to_string(&path);
+        if contents.is_err() {
+            println!("{:?}", contents.err().unwrap()); // third-party error
+            return Err(format!("IGNORE READ FAILURE: failed to read {:?}", path));
+        }
+        let mut ignore = PdfIgnore::default();
+        for line in contents.unwrap().lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("#") {
+                continue;
+            }
+            let dir_only = line.ends_with("/");
+            let line = line.trim_end_matches("/");
+            // patterns with a '/' match from the top of the dir, others match a name at any depth
+            let anchored = line.contains("/");
+            let pattern = Pattern::new(line.trim_start_matches("/"));
+            if pattern.is_err() {
+                println!("{}", pattern.err().unwrap()); // third-party error
+                return Err(format!("INVALID IGNORE PATTERN: '{}' in {:?} is not a valid glob", line, path));
+            }
+            ignore.rules.push(PdfIgnoreRule {
+                pattern: pattern.unwrap(),
+                dir_only: dir_only,
+                anchored: anchored,
+            });
+        }
+        return Ok(ignore);
+    }
+
+    /// `relative` is the path from the top of the receipt dir
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        if relative == Path::new(IGNORE_FILE) {
+            return true;
+        }
+        let name = relative.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let matched = if rule.anchored { rule.pattern.matches_path(relative) } else { rule.pattern.matches(&name) };
+            if matched {
+                return true;
+            }
+        }
+        return false;
+    }
+
+}
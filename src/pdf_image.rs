@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use genpdf::{elements, Alignment};
+use image::imageops::FilterType;
+use image::GenericImageView;
+
+use crate::pdf_invoice::new_document;
+
+pub const IMAGE_EXTENSIONS: [&str; 3] = ["jpg", "jpeg", "png"];
+
+// the printable area of an a4 page inside the 10mm margins, in inches
+const PAGE_WIDTH_INCHES: f64 = 190.0 / 25.4;
+const PAGE_HEIGHT_INCHES: f64 = 270.0 / 25.4;
+const MAX_DPI: f64 = 150.0;
+
+pub fn is_image_path(path: &Path) -> bool {
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    return IMAGE_EXTENSIONS.contains(&ext.as_str());
+}
+
+/// renders a photographed receipt as a single page pdf, scaled down to fit the page
+pub fn convert_image_to_pdf(source: &str, destination: &str) -> Result<(), String> {
+    let image = image::open(source);
+    if image.is_err() {
+        println!("{:?}", image.err().unwrap()); // third-party error
+        return Err(format!("IMAGE READ FAILURE: failed to decode the receipt image\n{}", source));
+    }
+    let mut image = image.unwrap();
+
+    // phone photos are stored uncompressed in the pdf, so they are scaled down to print resolution
+    let max_width = (PAGE_WIDTH_INCHES * MAX_DPI) as u32;
+    let max_height = (PAGE_HEIGHT_INCHES * MAX_DPI) as u32;
+    if image.width() > max_width || image.height() > max_height {
+        image = image.resize(max_width, max_height, FilterType::Triangle);
+    }
+    // pdf images cannot carry an alpha channel
+    let pixels = image.to_rgb8();
+    let dpi = (pixels.width() as f64 / PAGE_WIDTH_INCHES)
+        .max(pixels.height() as f64 / PAGE_HEIGHT_INCHES)
+        .max(72.0);
+
+    let element = elements::Image::from_dynamic_image(image::DynamicImage::ImageRgb8(pixels));
+    if element.is_err() {
+        println!("{:?}", element.err().unwrap()); // third-party error
+        return Err(format!("IMAGE CONVERSION FAILURE: failed to convert the receipt image to a pdf\n{}", source));
+    }
+    let title = Path::new(source).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let mut doc = new_document(&title);
+    doc.push(element.unwrap().with_dpi(dpi).with_alignment(Alignment::Center));
+
+    let output_file = doc.render_to_file(destination);
+    if output_file.is_err() {
+        println!("{:?}", output_file.err().unwrap()); // third-party error
+        return Err(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", destination));
+    }
+    return Ok(());
+}
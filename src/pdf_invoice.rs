@@ -1,30 +1,157 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use walkdir::WalkDir;
 use rust_decimal::Decimal;
-use genpdf::{elements, fonts::{self, FontData, FontFamily}, style, Alignment, Document, Element};
+use genpdf::{elements, fonts::FontFamily, style, Alignment, Document, Element};
+use serde::Serialize;
+
+use crate::pdf_allocate::{split_cost, PdfSplitOptions};
+use crate::pdf_chart::{PdfBarChart, PdfLineChart};
+use crate::pdf_duplicates::{PdfDuplicateOptions, PdfDuplicateReport};
+use crate::pdf_ignore::PdfIgnore;
+use crate::pdf_period::{PdfPeriod, PdfPeriodLength};
+use crate::pdf_sidecar::PdfSidecar;
+use crate::pdf_sort_manifest::PdfSortManifest;
+use crate::pdf_sort_summary::PdfSortSummary;
+use crate::pdf_verify::{PdfVerification, PdfVerifyOptions};
+
+/// the location field of a receipt name, with the .pdf extension
+pub const VALID_LOCATIONS: [&str; 3] = ["southroads.pdf", "utica.pdf", "split.pdf"];
+/// the kinds of file a receipt can be
+pub const RECEIPT_EXTENSIONS: [&str; 4] = ["pdf", "jpg", "jpeg", "png"];
+
+/// a location field like 'utica.pdf', photographed receipts keep their own extension
+pub fn is_valid_location(location: &str) -> bool {
+    let (name, ext) = location.rsplit_once(".").unwrap_or((location, ""));
+    return VALID_LOCATIONS.contains(&format!("{}.pdf", name).as_str()) && RECEIPT_EXTENSIONS.contains(&ext);
+}
+
+/// phones often save `IMG.JPG`, so the extension is matched in any case
+pub fn is_receipt_path(path: &Path) -> bool {
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    return RECEIPT_EXTENSIONS.contains(&ext.as_str());
+}
 
+/// the receipt a sidecar belongs to, whichever kind of file it is
+pub fn receipt_for_sidecar(sidecar: &Path) -> Option<PathBuf> {
+    return RECEIPT_EXTENSIONS
+        .iter()
+        .flat_map(|ext| [sidecar.with_extension(ext), sidecar.with_extension(ext.to_uppercase())])
+        .find(|path| path.is_file());
+}
 
+/// what each receipt costs each location, in the order given. split receipts take turns
+/// over any odd cent the same way `sort` shares them, so pass every receipt in the dir
+/// even when only some of them are wanted
+pub fn location_shares(line_items: &[PdfLineItem], split: &PdfSplitOptions) -> Result<Vec<Vec<(String, Decimal)>>, String> {
+    let mut all_shares: Vec<Vec<(String, Decimal)>> = vec![];
+    let mut turn = 0;
+    for item in line_items {
+        let location = item.location_name();
+        let mut shares = vec![(location.clone(), item.cost)];
+        if location == "split" {
+            shares = split_cost(item.cost, split, turn)?;
+            turn += 1;
+        }
+        all_shares.push(shares);
+    }
+    return Ok(all_shares);
+}
 
+/// a blank document with the fonts and page setup shared by every pdf finli renders
+pub fn new_document(title: &str) -> Document {
+
+    // loading in our fonts
+    let regular = include_bytes!("../fonts/LiberationSans-Regular.ttf") as &[u8];
+    let bold = include_bytes!("../fonts/LiberationSans-Bold.ttf");
+    let italic = include_bytes!("../fonts/LiberationSans-Italic.ttf");
+    let bold_italic = include_bytes!("../fonts/LiberationSans-BoldItalic.ttf");
+
+    let regular_data = genpdf::fonts::FontData::new(regular.to_vec(), None).expect("failed to load in the regular font");
+    let bold_data = genpdf::fonts::FontData::new(bold.to_vec(), None).expect("failed to load in the bold font");
+    let italic_data = genpdf::fonts::FontData::new(italic.to_vec(), None).expect("failed to load in the italic font");
+    let bold_italic_data = genpdf::fonts::FontData::new(bold_italic.to_vec(), None).expect("failed to load in the bold-italic font");
+
+    let font_family = FontFamily {
+        regular: regular_data,
+        bold: bold_data,
+        italic: italic_data,
+        bold_italic: bold_italic_data,
+    };
+
+    // prepare the pdf
+    // let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None);
+    // if font_family.is_err() {
+    //     let err = font_family.err().unwrap();
+    //     println!("{:?}", err); // third-party error
+    //     return Some(format!("THIRD PARTY FAILURE: failed to load the font family for pdf generation"));
+    // }
+    // let font_family = font_family.unwrap();
+    let mut doc = Document::new(font_family);
+    doc.set_title(title);
+    let mut decorator = genpdf::SimplePageDecorator::new();
+    decorator.set_margins(10);
+    doc.set_page_decorator(decorator);
+    doc.set_font_size(12);
+    return doc;
+}
+
+/// an invoice built from a dir of receipts, grouped by category
 pub struct PdfInvoice {
     pub expense_categories: Vec<PdfExpenseCategory>,
     pub total_cost: Decimal,
+    pub location_totals: Vec<(String, Decimal)>,
     pub file_name: String,
     pub name: String,
+    pub skipped: Vec<PdfSkippedFile>,
+    pub duplicates: PdfDuplicateReport,
+    pub excluded: Vec<String>,
+    pub charts: bool,
 }
 
 impl PdfInvoice {
 
-    pub fn new_from_dir(dir: &str, invoice_name: &str) -> Result<PdfInvoice, String> {
+    /// reads the receipts in `dir` and totals them up, nothing is rendered until `generate`
+    pub fn new_from_dir(dir: &str, invoice_name: &str, options: &PdfReadOptions, duplicate_options: &PdfDuplicateOptions, split: &PdfSplitOptions) -> Result<PdfInvoice, String> {
 
         // extract the line items
-        let line_items = PdfLineItem::new_from_dir(&dir);
+        let line_items = PdfLineItem::new_from_dir(dir, options);
         if line_items.is_err() {
             let err = line_items.err().unwrap();
             return Err(err);
         }
-        let line_items = line_items.unwrap();
+        let (line_items, skipped) = line_items.unwrap();
+
+        let mut pdf_invoice = PdfInvoice::new_from_line_items(invoice_name, line_items, duplicate_options, split)?;
+        pdf_invoice.skipped = skipped;
+        return Ok(pdf_invoice);
+
+    }
+
+    /// totals up receipts that were already read, split receipts shared out between just these receipts
+    pub fn new_from_line_items(invoice_name: &str, mut line_items: Vec<PdfLineItem>, duplicate_options: &PdfDuplicateOptions, split: &PdfSplitOptions) -> Result<PdfInvoice, String> {
+
+        // flagging scans of the same receipt, and keeping the extra copies out of the total when asked
+        let duplicates = PdfDuplicateReport::new(&line_items, duplicate_options.days)?;
+        let mut excluded: Vec<String> = vec![];
+        if duplicate_options.exclude {
+            excluded = duplicates.confirmed();
+            line_items.retain(|item| !excluded.contains(&item.path));
+        }
+
+        // what each location owes, split receipts shared out the same way sort does
+        let shares = location_shares(&line_items, split)?;
+        return Ok(PdfInvoice::new_from_shares(invoice_name, line_items, &shares, duplicates, excluded));
+
+    }
+
+    /// totals up receipts whose location shares were worked out already, in the same order as
+    /// `line_items`, e.g. one person's receipts shared out over the whole dir
+    pub fn new_from_shares(invoice_name: &str, line_items: Vec<PdfLineItem>, shares: &[Vec<(String, Decimal)>], duplicates: PdfDuplicateReport, excluded: Vec<String>) -> PdfInvoice {
+
+        let location_totals = PdfInvoice::location_totals(shares);
 
         // sort into categories
         let expense_categories = PdfExpenseCategory::new_from_line_items(line_items);
@@ -36,52 +163,72 @@ impl PdfInvoice {
         }
 
         // creating invoice
-        let pdf_invoice = PdfInvoice {
+        return PdfInvoice {
             name: invoice_name.to_string(),
             expense_categories: expense_categories,
             total_cost: invoice_total,
+            location_totals: location_totals,
             file_name:  format!("{}.pdf", invoice_name).to_lowercase().replace(" ", "_"),
+            skipped: vec![],
+            duplicates: duplicates,
+            excluded: excluded,
+            charts: false,
         };
 
-        return Ok(pdf_invoice);
+    }
 
+    fn location_totals(shares: &[Vec<(String, Decimal)>]) -> Vec<(String, Decimal)> {
+        let mut totals: Vec<(String, Decimal)> = vec![];
+        for (location, cost) in shares.iter().flatten() {
+            let total = totals.iter_mut().find(|(name, _)| name == location);
+            if total.is_some() {
+                total.unwrap().1 += *cost;
+            } else {
+                totals.push((location.clone(), *cost));
+            }
+        }
+        totals.sort();
+        return totals;
     }
 
+    /// the invoice total month by month, months with no receipts included so the trend has no gaps
+    pub fn monthly_totals(&self) -> Vec<(String, Decimal)> {
+        let mut totals: BTreeMap<i64, Decimal> = BTreeMap::new();
+        for item in self.expense_categories.iter().flat_map(|category| &category.line_items) {
+            let month = PdfPeriod::containing(&item.date, PdfPeriodLength::Month);
+            if month.is_ok() {
+                *totals.entry(month.unwrap().first_month).or_insert(Decimal::ZERO) += item.cost;
+            }
+        }
+        let first = totals.keys().next().copied().unwrap_or(0);
+        let last = totals.keys().last().copied().unwrap_or(-1);
+        return (first..=last)
+            .map(|first_month| {
+                let month = PdfPeriod { length: PdfPeriodLength::Month, first_month: first_month };
+                (month.to_string(), totals.get(&first_month).copied().unwrap_or(Decimal::ZERO))
+            })
+            .collect();
+    }
+
+    /// renders the invoice pdf to `file_name`
     pub fn generate(&self) -> Option<String> {
 
-		// loading in our fonts
-		let regular = include_bytes!("../fonts/LiberationSans-Regular.ttf") as &[u8];
-		let bold = include_bytes!("../fonts/LiberationSans-Bold.ttf");
-		let italic = include_bytes!("../fonts/LiberationSans-Italic.ttf");
-		let bold_italic = include_bytes!("../fonts/LiberationSans-BoldItalic.ttf");
-
-		let regular_data = genpdf::fonts::FontData::new(regular.to_vec(), None).expect("failed to load in the regular font");
-		let bold_data = genpdf::fonts::FontData::new(regular.to_vec(), None).expect("failed to load in the bold font");
-		let italic_data = genpdf::fonts::FontData::new(regular.to_vec(), None).expect("failed to load in the italic font");
-		let bold_italic_data = genpdf::fonts::FontData::new(regular.to_vec(), None).expect("failed to load in the bold-italic font");
-
-
-		let font_family = FontFamily {
-			regular: regular_data,
-			bold: bold_data,
-			italic: italic_data,
-			bold_italic: bold_italic_data,
-		};
-
-        // prepare the pdf
-        // let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None);
-        // if font_family.is_err() {
-        //     let err = font_family.err().unwrap();
-        //     println!("{:?}", err); // third-party error
-        //     return Some(format!("THIRD PARTY FAILURE: failed to load the font family for pdf generation"));
-        // }
-        // let font_family = font_family.unwrap();
-        let mut doc = Document::new(font_family);
-        doc.set_title(self.name.clone());
-        let mut decorator = genpdf::SimplePageDecorator::new();
-        decorator.set_margins(10);
-        doc.set_page_decorator(decorator);
-        doc.set_font_size(12);
+        let mut doc = new_document(&self.name);
+        self.render(&mut doc);
+
+        // writing output
+        let output_file = doc.render_to_file(self.file_name.clone());
+        if output_file.is_err() {
+            let err = output_file.err().unwrap();
+            println!("{:?}", err); // third-party error
+            return Some(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", self.file_name));
+        }
+
+        return None;
+    }
+
+    /// writes the title, categories and location totals into a document, so other reports can build on the invoice
+    pub fn render(&self, doc: &mut Document) {
 
         // write title header to invoice pdf
         let invoice_title = format!("{}: {}", self.name, self.total_cost);
@@ -116,19 +263,28 @@ impl PdfInvoice {
             doc.push(empty_paragraph);
         }
 
-        // writing output
-        let output_file = doc.render_to_file(self.file_name.clone());
-        if output_file.is_err() {
-            let err = output_file.err().unwrap();
-            println!("{:?}", err); // third-party error
-            return Some(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", self.file_name));
+        // writing the location totals
+        let header = elements::Paragraph::new("locations").aligned(Alignment::Left);
+        doc.push(header.styled(style::Style::new().bold().with_font_size(16)));
+        for (location, total) in &self.location_totals {
+            doc.push(elements::Paragraph::new(format!("{} => {}", location, total)).aligned(Alignment::Left));
         }
 
-        return None;
+        // the same totals as pictures, on a page of their own
+        if self.charts {
+            doc.push(elements::PageBreak::new());
+            let categories: Vec<(String, Decimal)> = self.expense_categories.iter().map(|category| (category.name.clone(), category.total_cost)).collect();
+            doc.push(PdfBarChart::new("spend by category", categories));
+            doc.push(elements::Break::new(1));
+            doc.push(PdfBarChart::new("spend by location", self.location_totals.clone()));
+            doc.push(elements::Break::new(1));
+            doc.push(PdfLineChart::new("spend by month", self.monthly_totals()));
+        }
     }
 
 }
 
+/// the receipts sharing one category and their total
 #[derive(Debug)]
 pub struct PdfExpenseCategory {
     pub name: String,
@@ -156,7 +312,7 @@ impl PdfExpenseCategory {
                 if *category != item.category {
                     continue;
                 }
-                total = total + item.cost;
+                total += item.cost;
                 matching_line_items.push(item.clone());
             }
             let expense_category = PdfExpenseCategory {
@@ -172,6 +328,39 @@ impl PdfExpenseCategory {
 
 }
 
+/// how forgiving reading a dir of receipts should be
+#[derive(Debug, Clone, Default)]
+pub struct PdfReadOptions {
+    pub recursive: bool,
+    pub skip_invalid: bool,
+    /// open every receipt as well, for commands that merge or copy them
+    pub verify: Option<PdfVerifyOptions>,
+}
+
+impl PdfReadOptions {
+
+    // records a file that is not a valid receipt, or fails when not skipping them
+    fn invalid(&self, skipped: &mut Vec<PdfSkippedFile>, path: &str, reason: &str) -> Result<(), String> {
+        if !self.skip_invalid {
+            return Err(reason.to_owned());
+        }
+        skipped.push(PdfSkippedFile {
+            path: path.to_owned(),
+            reason: reason.lines().next().unwrap_or("").to_owned(),
+        });
+        return Ok(());
+    }
+
+}
+
+/// a file passed over by --skip-invalid and why
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfSkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// one receipt, parsed from a `[DATE]-[VENDOR]-[COST]-[DESCRIPTION]-[CATEGORY]-[LOCATION]` file name
 #[derive(Debug)]
 pub struct PdfLineItem {
     pub source_dir: String,
@@ -184,6 +373,14 @@ pub struct PdfLineItem {
     pub description: String,
     pub category: String,
     pub location: String,
+    pub notes: Option<String>,
+    pub attendees: Vec<String>,
+    pub mileage: Option<Decimal>,
+    pub approval_status: Option<String>,
+    pub paid_by: Option<String>,
+    pub card: Option<String>,
+    pub submitter: Option<String>,
+    pub sidecar: Option<PdfSidecar>,
 }
 
 impl Clone for PdfLineItem {
@@ -199,15 +396,26 @@ impl Clone for PdfLineItem {
             description: self.description.clone(),
             category: self.category.clone(),
             location: self.location.clone(),
+            notes: self.notes.clone(),
+            attendees: self.attendees.clone(),
+            mileage: self.mileage,
+            approval_status: self.approval_status.clone(),
+            paid_by: self.paid_by.clone(),
+            card: self.card.clone(),
+            submitter: self.submitter.clone(),
+            sidecar: self.sidecar.clone(),
         }
     }
 }
 
 impl PdfLineItem {
+    /// parses the receipt at `path`, only the file name is read
     pub fn new(source_dir: &str, path: &str) -> Result<PdfLineItem, String> {
         // ensuring our pdf file has 6 lines
         let trimmed_path = &path[(source_dir.len() + 1)..path.len()];
-        let parts: Vec<String> = trimmed_path.split("-").map(|s| s.to_string()).collect();
+        // receipts in subdirectories are named the same way, only the file name is parsed
+        let file_name = trimmed_path.rsplit("/").next().unwrap_or(trimmed_path);
+        let parts: Vec<String> = file_name.split("-").map(|s| s.to_string()).collect();
         if parts.len() != 6 {
             return Err(format!("INVALID FILE NAME: PdfLineItem must consist of 6 distinct parts but you provided {}\n{}", parts.len(), trimmed_path).to_owned());
         }
@@ -215,7 +423,7 @@ impl PdfLineItem {
         // ensuring we have a valid date
         let date_str = parts[0].to_owned();
         let date_num = date_str.parse::<i32>();
-        if !date_num.is_ok() {
+        if date_num.is_err() {
             return Err(format!(
                 "INVALID DATE: PdfLineItem 'date' field should be a valid number\n{}",
                 trimmed_path
@@ -245,7 +453,7 @@ impl PdfLineItem {
         // converting the cost (as a String) into a Decimal
         let cost_as_decimal = Decimal::from_str(&cost);
         if cost_as_decimal.is_err() {
-            let err = cost_as_decimal.unwrap();
+            let err = cost_as_decimal.err().unwrap();
             println!("{:?}", err); // third party library error
             return Err(format!("CONVERSION FAILURE: failed to convert the 'cost' field into a Decimal fit for accurate financial math\n{}", trimmed_path));
         }
@@ -256,10 +464,9 @@ impl PdfLineItem {
         let category = parts[4].to_owned();
 
         // extracting the location and ensuring we have a valid name
-        let valid_locations = vec!["southroads.pdf", "utica.pdf", "split.pdf"];
         let location = parts[5].to_owned();
         let location = location.to_lowercase();
-        if !valid_locations.contains(&location.as_str()) {
+        if !is_valid_location(&location) {
             return Err(format!("INVALID LOCATION: the 'location' field must be 'southroads', 'utica', or 'split'\n{}", trimmed_path));
         }
 
@@ -274,13 +481,36 @@ impl PdfLineItem {
             description: description,
             category: category,
             location: location,
+            notes: None,
+            attendees: vec![],
+            mileage: None,
+            approval_status: None,
+            paid_by: None,
+            card: None,
+            submitter: None,
+            sidecar: None,
         };
         return Ok(line_item);
     }
 
-    pub fn new_from_dir(source_dir: &str) -> Result<Vec<PdfLineItem>, String> {
+    /// a line item with the optional sidecar next to the receipt merged in
+    pub fn new_with_sidecar(source_dir: &str, path: &str) -> Result<PdfLineItem, String> {
+        let mut line_item = PdfLineItem::new(source_dir, path)?;
+        let sidecar = PdfSidecar::new_for_receipt(path)?;
+        if sidecar.is_some() {
+            let err = sidecar.unwrap().apply(&mut line_item);
+            if err.is_some() {
+                return Err(err.unwrap());
+            }
+        }
+        return Ok(line_item);
+    }
+
+    /// reads every receipt in the dir, optionally walking subdirectories and
+    /// passing over files that are not receipts instead of failing on them
+    pub fn new_from_dir(source_dir: &str, options: &PdfReadOptions) -> Result<(Vec<PdfLineItem>, Vec<PdfSkippedFile>), String> {
         // ensure we have a valid source dir
-        let dir_path = Path::new(&source_dir);
+        let dir_path = Path::new(source_dir);
         if !dir_path.exists() {
             return Err(format!(
                 "MISSING DIR: this dir does not exist: {:?}",
@@ -294,9 +524,21 @@ impl PdfLineItem {
             ));
         }
 
+        // anything matching the .finliignore is never looked at
+        let ignore = PdfIgnore::new_from_dir(source_dir)?;
+        let mut walker = WalkDir::new(dir_path).sort_by_file_name();
+        if !options.recursive {
+            walker = walker.max_depth(1);
+        }
+        let walker = walker.into_iter().filter_entry(|entry| {
+            let relative = entry.path().strip_prefix(dir_path).unwrap_or(entry.path());
+            return entry.depth() == 0 || !ignore.is_ignored(relative, entry.file_type().is_dir());
+        });
+
         // extract all the file paths within
         let mut file_paths: Vec<String> = vec![];
-        for entry in WalkDir::new(dir_path) {
+        let mut skipped: Vec<PdfSkippedFile> = vec![];
+        for entry in walker {
             if entry.is_err() {
                 println!("{:?}", entry.err()); // print third party error
                 return Err(
@@ -311,25 +553,36 @@ impl PdfLineItem {
                 return Err("CONVERSION ERROR: failed to convert the filepath to a &str".to_owned());
             }
             let path_str = path_str.unwrap().to_owned();
-            if path_str == source_dir {
+            if entry.depth() == 0 {
                 // skip the provided dir
                 continue;
             }
             if path.is_dir() {
-                return Err("INVALID DIR CONTENTS: the provided file path must not contain any subdirectories".to_owned());
+                if options.recursive {
+                    continue;
+                }
+                options.invalid(&mut skipped, &path_str, "INVALID DIR CONTENTS: the provided file path must not contain any subdirectories, use --recursive to read them")?;
+                continue;
             }
             let ext = path.extension();
             if ext.is_none() {
-                return Err(
-                    "INVALID DIR CONTENT: the dir must contain files with valid extensions"
-                        .to_owned(),
-                );
+                options.invalid(&mut skipped, &path_str, "INVALID DIR CONTENT: the dir must contain files with valid extensions")?;
+                continue;
             }
-            let ext = ext.unwrap();
-            if ext != "pdf" {
-                return Err(
-                    "INVALID FILE EXTENSION: the dir must contain only .pdf files".to_owned(),
-                );
+            if PdfSortSummary::is_summary_path(path) || PdfSortManifest::is_manifest_path(path) {
+                // the report a sort leaves in each location folder and its manifest in the output root
+                continue;
+            }
+            if PdfSidecar::is_sidecar_path(path) {
+                // sidecars are picked up alongside their receipt below
+                if receipt_for_sidecar(path).is_none() {
+                    options.invalid(&mut skipped, &path_str, &format!("ORPHAN SIDECAR: sidecar file has no matching receipt\n{}", path_str))?;
+                }
+                continue;
+            }
+            if !is_receipt_path(path) {
+                options.invalid(&mut skipped, &path_str, "INVALID FILE EXTENSION: the dir must contain only .pdf, .jpg, .jpeg or .png files")?;
+                continue;
             }
             file_paths.push(path_str);
         }
@@ -337,48 +590,88 @@ impl PdfLineItem {
         // take each file path and create a PdfLineItem for each
         let mut line_items: Vec<PdfLineItem> = vec![];
         for path in file_paths {
-            let line_item = PdfLineItem::new(&source_dir, &path);
+            let line_item = PdfLineItem::new_with_sidecar(source_dir, &path);
             if line_item.is_err() {
-                return Err(line_item.err().unwrap());
+                options.invalid(&mut skipped, &path, &line_item.err().unwrap())?;
+                continue;
             }
-            let line_item = line_item.unwrap();
-            line_items.push(line_item);
+            if options.verify.is_some() {
+                let verification = PdfVerification::new(&path, options.verify.as_ref().unwrap());
+                if verification.is_err() {
+                    options.invalid(&mut skipped, &path, &verification.err().unwrap())?;
+                    continue;
+                }
+            }
+            line_items.push(line_item.unwrap());
         }
 
-        return Ok(line_items);
+        return Ok((line_items, skipped));
+    }
+
+    /// the parts of the MMDDYY date, with the year in full
+    pub fn month(&self) -> String {
+        return self.date[0..2].to_owned();
+    }
+
+    pub fn day(&self) -> String {
+        return self.date[2..4].to_owned();
+    }
+
+    pub fn year(&self) -> String {
+        return format!("20{}", &self.date[4..6]);
+    }
+
+    /// the location without the file extension, e.g. 'utica'
+    pub fn location_name(&self) -> String {
+        return self.location.rsplit_once(".").map(|(name, _)| name).unwrap_or(&self.location).to_owned();
+    }
+
+    pub fn extension(&self) -> String {
+        return self.location.rsplit_once(".").map(|(_, ext)| ext).unwrap_or("pdf").to_owned();
+    }
+
+    /// the canonical file name for the fields as they stand, sidecar overrides included
+    pub fn file_name(&self) -> String {
+        return format!("{}-{}-{}-{}-{}-{}", self.date, self.vendor, self.cost, self.description, self.category, self.location);
     }
 
     pub fn set_cost(&mut self, new_cost: Decimal) {
-        let old_cost = self.cost.to_string();
-        let original_path = self.path.clone();
         self.cost = new_cost;
-        self.path = original_path.replace(&format!("-{}-", old_cost), &format!("-{}-", new_cost));
-        self.trimmed_path = original_path.replace(&format!("-{}-", old_cost), &format!("-{}-", &new_cost.to_string()));
+        self.rename();
     }
 
 
     pub fn set_location(&mut self, new_location: String) -> Option<String> {
         let new_location = new_location.to_lowercase();
-        let valid_locations = vec!["southroads.pdf", "utica.pdf", "split.pdf"];
-        if !valid_locations.contains(&new_location.as_str()) {
+        if !is_valid_location(&new_location) {
             return Some(format!(
                 "INVALID LOCATION: the 'location' field must be 'southroads', 'utica', or 'split'\n{}",
                 self.trimmed_path
             ));
         }
-        let old_location = self.location.clone();
-        let original_path = self.path.clone();
-        self.location = new_location.clone();
-        self.path = original_path.replace(&format!("-{}", old_location), &format!("-{}", new_location));
-        self.trimmed_path = original_path.replace(&format!("-{}", old_location), &format!("-{}", new_location));
+        self.location = new_location;
+        self.rename();
         return None
     }
 
+    // the paths follow the fields rather than patching the old name, which may not hold them
+    fn rename(&mut self) {
+        let file_name = self.file_name();
+        let with_file_name = |path: &str| match path.rsplit_once("/") {
+            Some((dir, _)) => format!("{}/{}", dir, file_name),
+            None => file_name.clone(),
+        };
+        self.path = with_file_name(&self.path);
+        self.trimmed_path = with_file_name(&self.trimmed_path);
+    }
+
+    /// moves the line item under another dir, keeping its path inside the old one
+    #[deprecated(since = "0.2.0", note = "sort destinations come from the layout now, use `pdf_sort_plan::layout_destination`")]
     pub fn set_source_dir(&mut self, new_source: &str) {
         let old_source = self.source_dir.clone();
         let old_path = self.path.clone();
         self.source_dir = new_source.to_owned();
-        self.path = self.source_dir.clone() + &old_path[old_source.len()..old_path.len()].to_owned();
+        self.path = self.source_dir.clone() + &old_path[old_source.len()..old_path.len()];
     }
 
 
@@ -0,0 +1,126 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::pdf_duplicates::day_number;
+
+/// how long a reporting period runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PdfPeriodLength {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl PdfPeriodLength {
+    pub fn months(&self) -> i64 {
+        match self {
+            PdfPeriodLength::Month => 1,
+            PdfPeriodLength::Quarter => 3,
+            PdfPeriodLength::Year => 12,
+        }
+    }
+}
+
+/// months since january of year 0 for an MMDDYY date
+pub fn month_number(date: &str) -> Option<i64> {
+    day_number(date)?;
+    let month: i64 = date[0..2].parse().ok()?;
+    let year: i64 = 2000 + date[4..6].parse::<i64>().ok()?;
+    return Some(year * 12 + month - 1);
+}
+
+/// a calendar month, quarter or year, written `MMYY`, `qNYY` or `20YY`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfPeriod {
+    pub length: PdfPeriodLength,
+    pub first_month: i64,
+}
+
+impl PdfPeriod {
+
+    /// the period of the given length a receipt date falls in
+    pub fn containing(date: &str, length: PdfPeriodLength) -> Result<PdfPeriod, String> {
+        let month = month_number(date);
+        if month.is_none() {
+            return Err(format!("INVALID DATE: '{}' is not a MMDDYY date", date));
+        }
+        let month = month.unwrap();
+        let period = PdfPeriod {
+            length: length,
+            first_month: month - month % length.months(),
+        };
+        return Ok(period);
+    }
+
+    /// the period of the given length holding the latest of the dates
+    pub fn latest(dates: &[&str], length: PdfPeriodLength) -> Option<PdfPeriod> {
+        let latest = dates.iter().filter(|date| month_number(date).is_some()).max_by_key(|date| month_number(date))?;
+        return PdfPeriod::containing(latest, length).ok();
+    }
+
+    /// the period of the same length just before this one
+    pub fn previous(&self) -> PdfPeriod {
+        return PdfPeriod {
+            length: self.length,
+            first_month: self.first_month - self.length.months(),
+        };
+    }
+
+    /// the same period a year earlier
+    pub fn year_earlier(&self) -> PdfPeriod {
+        return PdfPeriod {
+            length: self.length,
+            first_month: self.first_month - 12,
+        };
+    }
+
+    pub fn contains(&self, date: &str) -> bool {
+        let month = month_number(date);
+        return month.is_some() && (self.first_month..self.first_month + self.length.months()).contains(&month.unwrap());
+    }
+
+    pub fn months(&self) -> i64 {
+        return self.length.months();
+    }
+
+}
+
+impl FromStr for PdfPeriod {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<PdfPeriod, String> {
+        let value = value.trim().to_lowercase();
+        let invalid = format!("INVALID PERIOD: '{}' should be a month like '0125', a quarter like 'q125' or a year like '2025'", value);
+        if value.len() != 4 || !value.chars().skip(1).all(|c| c.is_ascii_digit()) {
+            return Err(invalid);
+        }
+        let year: i64 = 2000 + value[2..4].parse::<i64>().unwrap(); // checked above
+        if value.starts_with("q") {
+            let quarter: i64 = value[1..2].parse().unwrap(); // checked above
+            if !(1..=4).contains(&quarter) {
+                return Err(invalid);
+            }
+            return Ok(PdfPeriod { length: PdfPeriodLength::Quarter, first_month: year * 12 + (quarter - 1) * 3 });
+        }
+        if value.starts_with("20") {
+            return Ok(PdfPeriod { length: PdfPeriodLength::Year, first_month: year * 12 });
+        }
+        let month = value[0..2].parse::<i64>();
+        if month.is_err() || !(1..=12).contains(month.as_ref().unwrap()) {
+            return Err(invalid);
+        }
+        return Ok(PdfPeriod { length: PdfPeriodLength::Month, first_month: year * 12 + month.unwrap() - 1 });
+    }
+}
+
+impl fmt::Display for PdfPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let year = self.first_month / 12;
+        let month = self.first_month % 12 + 1;
+        match self.length {
+            PdfPeriodLength::Month => write!(f, "{:02}{:02}", month, year % 100),
+            PdfPeriodLength::Quarter => write!(f, "q{}{:02}", (month - 1) / 3 + 1, year % 100),
+            PdfPeriodLength::Year => write!(f, "{}", year),
+        }
+    }
+}
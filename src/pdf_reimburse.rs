@@ -0,0 +1,216 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use genpdf::{elements, style, Alignment, Element};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use rust_decimal::Decimal;
+
+use crate::pdf_allocate::PdfSplitOptions;
+use crate::pdf_duplicates::{PdfDuplicateOptions, PdfDuplicateReport};
+use crate::pdf_image::{convert_image_to_pdf, is_image_path};
+use crate::pdf_invoice::{location_shares, new_document, PdfInvoice, PdfLineItem, PdfReadOptions, PdfSkippedFile};
+
+// page attributes a page can inherit from the page tree above it
+const INHERITED_PAGE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// an expense report for one person's receipts, with the receipts attached after it
+pub struct PdfReimbursement {
+    pub submitter: String,
+    pub invoice: PdfInvoice,
+}
+
+impl PdfReimbursement {
+
+    /// one report per submitter, receipts without a `submitter` sidecar field are left out.
+    /// duplicates are left out and split receipts shared over the whole dir before the receipts
+    /// are grouped by person, so each location is charged what `sort` gives it
+    pub fn new_from_dir(dir: &str, report_name: &str, options: &PdfReadOptions, duplicate_options: &PdfDuplicateOptions, split: &PdfSplitOptions) -> Result<(Vec<PdfReimbursement>, Vec<PdfSkippedFile>), String> {
+        let (line_items, skipped) = PdfLineItem::new_from_dir(dir, options)?;
+        let mut excluded: Vec<String> = vec![];
+        if duplicate_options.exclude {
+            excluded = PdfDuplicateReport::new(&line_items, duplicate_options.days)?.confirmed();
+        }
+        let kept: Vec<PdfLineItem> = line_items.iter().filter(|item| !excluded.contains(&item.path)).cloned().collect();
+        let all_shares = location_shares(&kept, split)?;
+
+        let mut submitters: Vec<String> = line_items.iter().filter_map(|item| item.submitter.clone()).collect();
+        submitters.sort();
+        submitters.dedup();
+
+        let mut reimbursements: Vec<PdfReimbursement> = vec![];
+        for submitter in submitters {
+            let is_theirs = |item: &PdfLineItem| item.submitter.as_ref() == Some(&submitter);
+
+            // the duplicate check still runs on just their receipts
+            let submitted: Vec<PdfLineItem> = line_items.iter().filter(|item| is_theirs(item)).cloned().collect();
+            let duplicates = PdfDuplicateReport::new(&submitted, duplicate_options.days)?;
+            let their_excluded: Vec<String> = submitted.iter().map(|item| item.path.clone()).filter(|path| excluded.contains(path)).collect();
+
+            let (items, shares): (Vec<PdfLineItem>, Vec<Vec<(String, Decimal)>>) = kept
+                .iter()
+                .zip(&all_shares)
+                .filter(|(item, _)| is_theirs(item))
+                .map(|(item, shares)| (item.clone(), shares.clone()))
+                .unzip();
+            let name = format!("{} {}", report_name, submitter);
+            let invoice = PdfInvoice::new_from_shares(&name, items, &shares, duplicates, their_excluded);
+            reimbursements.push(PdfReimbursement {
+                submitter: submitter,
+                invoice: invoice,
+            });
+        }
+        return Ok((reimbursements, skipped));
+    }
+
+    /// the receipts in the order the report lists them, which is the order they are attached in
+    pub fn receipts(&self) -> Vec<String> {
+        return self.invoice.expense_categories
+            .iter()
+            .flat_map(|category| category.line_items.iter().map(|item| item.path.clone()))
+            .collect();
+    }
+
+    /// renders the report with its approval block to the invoice's `file_name`, followed by every receipt
+    pub fn generate(&self) -> Result<(), String> {
+        let mut doc = new_document(&self.invoice.name);
+        self.invoice.render(&mut doc);
+
+        // the approval block
+        doc.push(elements::Break::new(2));
+        let header = elements::Paragraph::new("approval").aligned(Alignment::Left);
+        doc.push(header.styled(style::Style::new().bold().with_font_size(16)));
+        doc.push(elements::Paragraph::new(format!("submitted by: {}", self.submitter)));
+        doc.push(elements::Break::new(1));
+        doc.push(elements::Paragraph::new("signature: ______________________________    date: ____________"));
+        doc.push(elements::Break::new(1));
+        doc.push(elements::Paragraph::new("approved by: ______________________________    date: ____________"));
+        doc.push(elements::Break::new(1));
+        doc.push(elements::Paragraph::new("signature: ______________________________    date: ____________"));
+        doc.push(elements::Break::new(2));
+        let appendix = format!("the {} receipts listed above are attached in the same order", self.receipts().len());
+        doc.push(elements::Paragraph::new(appendix).styled(style::Style::new().italic()));
+
+        let mut rendered: Vec<u8> = vec![];
+        let result = doc.render(&mut rendered);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", self.invoice.file_name));
+        }
+        let report = Document::load_mem(&rendered);
+        if report.is_err() {
+            println!("{:?}", report.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to reopen the rendered report: {}", self.invoice.file_name));
+        }
+        let mut report = report.unwrap();
+
+        // the appendix
+        for receipt in self.receipts() {
+            let attachment = load_receipt(&receipt)?;
+            append_pages(&mut report, attachment, &receipt)?;
+        }
+
+        let result = report.save(&self.invoice.file_name);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to write output pdf file: {}", self.invoice.file_name));
+        }
+        return Ok(());
+    }
+
+}
+
+// a receipt as a pdf, photographed receipts are converted on the way in
+fn load_receipt(path: &str) -> Result<Document, String> {
+    let mut pdf_path = path.to_owned();
+    if is_image_path(Path::new(path)) {
+        pdf_path = env::temp_dir().join(format!("finli-appendix-{}.pdf", process::id())).to_string_lossy().to_string();
+        convert_image_to_pdf(path, &pdf_path)?;
+    }
+    let doc = Document::load(&pdf_path);
+    if pdf_path != path {
+        let _ = fs::remove_file(&pdf_path);
+    }
+    if doc.is_err() {
+        println!("{:?}", doc.err().unwrap()); // third-party error
+        return Err(format!("APPENDIX FAILURE: failed to read the receipt to attach it\n{}", path));
+    }
+    let doc = doc.unwrap();
+    if doc.is_encrypted() {
+        return Err(format!("APPENDIX FAILURE: password protected receipts cannot be attached\n{}", path));
+    }
+    return Ok(doc);
+}
+
+// moves every page of `attachment` onto the end of `report`
+fn append_pages(report: &mut Document, mut attachment: Document, path: &str) -> Result<(), String> {
+    let pages_id = report.catalog().and_then(|catalog| catalog.get(b"Pages")).and_then(|pages| pages.as_reference());
+    if pages_id.is_err() {
+        println!("{:?}", pages_id.err().unwrap()); // third-party error
+        return Err("APPENDIX FAILURE: the rendered report has no page tree".to_owned());
+    }
+    let pages_id = pages_id.unwrap();
+
+    // keeping the attachment's object ids clear of the report's
+    attachment.renumber_objects_with(report.max_id + 1);
+    report.max_id = report.max_id.max(attachment.max_id);
+
+    // pages are hung straight off the report's page tree, so anything they inherited is copied onto them
+    let mut kids: Vec<Object> = vec![];
+    for page_id in attachment.get_pages().into_values() {
+        let page = attachment.get_dictionary(page_id);
+        if page.is_err() {
+            println!("{:?}", page.err().unwrap()); // third-party error
+            return Err(format!("APPENDIX FAILURE: failed to read a page of the receipt\n{}", path));
+        }
+        let mut page = page.unwrap().clone();
+        for key in INHERITED_PAGE_KEYS {
+            if page.has(key) {
+                continue;
+            }
+            let value = inherited(&attachment, &page, key);
+            if value.is_some() {
+                page.set(key, value.unwrap());
+            }
+        }
+        page.set("Parent", pages_id);
+        attachment.objects.insert(page_id, Object::Dictionary(page));
+        kids.push(Object::Reference(page_id));
+    }
+
+    // the attachment's own catalog and page tree are left behind
+    for (id, object) in attachment.objects {
+        let type_name = object.type_name().unwrap_or(b"");
+        if type_name == b"Catalog" || type_name == b"Pages" {
+            continue;
+        }
+        report.objects.insert(id, object);
+    }
+
+    let pages = report.get_dictionary_mut(pages_id);
+    if pages.is_err() {
+        println!("{:?}", pages.err().unwrap()); // third-party error
+        return Err("APPENDIX FAILURE: the rendered report has no page tree".to_owned());
+    }
+    let pages = pages.unwrap();
+    let count = pages.get(b"Count").and_then(|count| count.as_i64()).unwrap_or(0);
+    pages.set("Count", count + kids.len() as i64);
+    let mut all_kids = pages.get(b"Kids").and_then(|kids| kids.as_array()).cloned().unwrap_or_default();
+    all_kids.extend(kids);
+    pages.set("Kids", all_kids);
+    return Ok(());
+}
+
+// the nearest value for `key` up the page tree from a page
+fn inherited(doc: &Document, page: &Dictionary, key: &[u8]) -> Option<Object> {
+    let mut parent: Option<ObjectId> = page.get(b"Parent").and_then(|parent| parent.as_reference()).ok();
+    while parent.is_some() {
+        let node = doc.get_dictionary(parent.unwrap()).ok()?;
+        if let Ok(value) = node.get(key) {
+            return Some(value.clone());
+        }
+        parent = node.get(b"Parent").and_then(|parent| parent.as_reference()).ok();
+    }
+    return None;
+}
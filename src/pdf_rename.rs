@@ -0,0 +1,326 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::pdf_add::PdfKnownValues;
+use crate::pdf_config::PdfConfig;
+use crate::pdf_invoice::{PdfLineItem, RECEIPT_EXTENSIONS};
+use crate::pdf_sidecar::{PdfSidecar, PdfSidecarFormat};
+use crate::pdf_suggest::PdfSuggestion;
+
+pub const DEFAULT_JOURNAL_FILE: &str = "finli-rename-journal.json";
+
+/// one file renamed from `from` to `to`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// every rename that would bring a dir to canonical names
+#[derive(Debug)]
+pub struct PdfRenamePlan {
+    pub dir: String,
+    pub renames: Vec<PdfRename>,
+    pub unchanged: Vec<String>,
+    pub failures: Vec<String>,
+    pub mismatches: Vec<String>,
+}
+
+impl PdfRenamePlan {
+
+    /// with `suggest` the text of each receipt fills in dates and amounts the name is missing
+    pub fn new(dir: &str, config: &PdfConfig, suggest: bool) -> Result<PdfRenamePlan, String> {
+
+        // ensure we have a valid dir
+        let dir_path = Path::new(dir);
+        if !dir_path.is_dir() {
+            return Err(format!("MISSING DIR: this dir does not exist: {:?}", dir_path));
+        }
+
+        // collecting the receipts, sidecars are renamed along with them
+        let entries = fs::read_dir(dir_path);
+        if entries.is_err() {
+            println!("{:?}", entries.err().unwrap()); // third-party error
+            return Err(format!("READ DIR FAILURE: failed to read the contents of {}", dir));
+        }
+        let mut file_names: Vec<String> = vec![];
+        for entry in entries.unwrap() {
+            if entry.is_err() {
+                println!("{:?}", entry.err().unwrap()); // third-party error
+                return Err(format!("READ DIR FAILURE: failed to read an entry of {}", dir));
+            }
+            let path = entry.unwrap().path();
+            let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            if !path.is_file() || !RECEIPT_EXTENSIONS.contains(&ext.to_lowercase().as_str()) {
+                continue;
+            }
+            let file_name = path.file_name().and_then(|name| name.to_str());
+            if file_name.is_none() {
+                return Err("CONVERSION ERROR: failed to convert the filepath to a &str".to_owned());
+            }
+            file_names.push(file_name.unwrap().to_owned());
+        }
+        file_names.sort();
+
+        // working out the new name for each receipt
+        let mut plan = PdfRenamePlan {
+            dir: dir.to_owned(),
+            renames: vec![],
+            unchanged: vec![],
+            failures: vec![],
+            mismatches: vec![],
+        };
+        let mut known_vendors: Vec<String> = vec![];
+        if suggest {
+            known_vendors = PdfKnownValues::new_from_dir(dir).vendors;
+        }
+        for file_name in file_names {
+            let mut suggestion: Option<PdfSuggestion> = None;
+            if suggest {
+                let path = format!("{}/{}", dir, file_name);
+                suggestion = PdfSuggestion::new(&path, config, &known_vendors).ok();
+            }
+            if let Some(suggestion) = &suggestion {
+                if suggestion.amount_mismatch() {
+                    plan.mismatches.push(format!("AMOUNT MISMATCH: the name says {} but the document shows {}\n{}", suggestion.file_cost.unwrap(), suggestion.total.unwrap(), file_name));
+                }
+            }
+            let normalized = normalize_file_name(dir, &file_name, config, suggestion.as_ref());
+            if normalized.is_err() {
+                plan.failures.push(normalized.err().unwrap());
+                continue;
+            }
+            let normalized = normalized.unwrap();
+            if normalized == file_name {
+                plan.unchanged.push(file_name);
+                continue;
+            }
+            let from = dir_path.join(&file_name).to_string_lossy().to_string();
+            let to = dir_path.join(&normalized).to_string_lossy().to_string();
+            for format in [PdfSidecarFormat::Toml, PdfSidecarFormat::Json] {
+                let sidecar_from = PdfSidecar::path_for_receipt(&from, &format);
+                if Path::new(&sidecar_from).is_file() {
+                    plan.renames.push(PdfRename {
+                        from: sidecar_from,
+                        to: PdfSidecar::path_for_receipt(&to, &format),
+                    });
+                }
+            }
+            plan.renames.push(PdfRename { from: from, to: to });
+        }
+
+        // two receipts normalizing to the same name would clobber each other
+        for (i, rename) in plan.renames.iter().enumerate() {
+            let clashes = plan.renames[..i].iter().any(|other| other.to == rename.to);
+            // a case-only rename reports itself as existing on case-insensitive filesystems
+            let taken = Path::new(&rename.to).exists() && rename.to.to_lowercase() != rename.from.to_lowercase();
+            if clashes || taken {
+                return Err(format!("RENAME COLLISION: {} already exists or more than one file would be renamed to it", rename.to));
+            }
+        }
+
+        return Ok(plan);
+    }
+
+    pub fn execute(&self, journal_path: &str) -> Result<(), String> {
+        let mut done: Vec<PdfRename> = vec![];
+        let mut failure: Option<String> = None;
+        for rename in &self.renames {
+            let result = fs::rename(&rename.from, &rename.to);
+            if result.is_err() {
+                println!("{:?}", result.err().unwrap()); // third-party error
+                failure = Some(format!("RENAME FAILURE: failed to rename {} to {}", rename.from, rename.to));
+                break;
+            }
+            done.push(rename.clone());
+        }
+
+        // whatever made it to disk is journaled so it can be undone
+        if !done.is_empty() {
+            let mut journal = PdfRenameJournal::new_from_file(journal_path)?;
+            journal.runs.push(PdfRenameRun {
+                dir: self.dir.clone(),
+                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                renames: done,
+            });
+            journal.save(journal_path)?;
+        }
+
+        if failure.is_some() {
+            return Err(failure.unwrap());
+        }
+        return Ok(());
+    }
+
+}
+
+/// the renames made by one run of `finli rename`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfRenameRun {
+    pub dir: String,
+    pub timestamp: u64,
+    pub renames: Vec<PdfRename>,
+}
+
+/// every rename run, kept so they can be undone
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PdfRenameJournal {
+    pub runs: Vec<PdfRenameRun>,
+}
+
+impl PdfRenameJournal {
+
+    pub fn new_from_file(path: &str) -> Result<PdfRenameJournal, String> {
+        if !Path::new(path).exists() {
+            return Ok(PdfRenameJournal::default());
+        }
+        let contents = fs::read_to_string(path);
+        if contents.is_err() {
+            println!("{:?}", contents.err().unwrap()); // third-party error
+            return Err(format!("JOURNAL READ FAILURE: failed to read rename journal {}", path));
+        }
+        let journal = serde_json::from_str::<PdfRenameJournal>(&contents.unwrap());
+        if journal.is_err() {
+            println!("{}", journal.err().unwrap()); // third-party error
+            return Err(format!("INVALID JOURNAL: failed to parse rename journal {}", path));
+        }
+        return Ok(journal.unwrap());
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self);
+        if contents.is_err() {
+            println!("{}", contents.err().unwrap()); // third-party error
+            return Err(format!("JOURNAL WRITE FAILURE: failed to serialize rename journal {}", path));
+        }
+        let result = fs::write(path, contents.unwrap());
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("JOURNAL WRITE FAILURE: failed to write rename journal {}", path));
+        }
+        return Ok(());
+    }
+
+    /// reverses the most recent run for the given dir and drops it from the journal
+    pub fn undo(path: &str, dir: &str) -> Result<PdfRenameRun, String> {
+        let mut journal = PdfRenameJournal::new_from_file(path)?;
+        let index = journal.runs.iter().rposition(|run| run.dir == dir);
+        if index.is_none() {
+            return Err(format!("NOTHING TO UNDO: the journal {} has no renames recorded for {}", path, dir));
+        }
+        let run = journal.runs.remove(index.unwrap());
+
+        // checking every rename can be reversed before touching anything
+        for rename in &run.renames {
+            if !Path::new(&rename.to).exists() || Path::new(&rename.from).exists() {
+                return Err(format!("UNDO FAILURE: cannot restore {} to {}, the files have changed since the rename", rename.to, rename.from));
+            }
+        }
+        for rename in run.renames.iter().rev() {
+            let result = fs::rename(&rename.to, &rename.from);
+            if result.is_err() {
+                println!("{:?}", result.err().unwrap()); // third-party error
+                return Err(format!("RENAME FAILURE: failed to rename {} to {}", rename.to, rename.from));
+            }
+        }
+
+        journal.save(path)?;
+        return Ok(run);
+    }
+
+}
+
+/// turns a loosely typed receipt name into the canonical
+/// `[DATE]-[VENDOR]-[COST]-[DESCRIPTION]-[CATEGORY]-[LOCATION].[EXT]` form,
+/// a suggestion fills in a date or amount that cannot be read from the name
+pub fn normalize_file_name(dir: &str, file_name: &str, config: &PdfConfig, suggestion: Option<&PdfSuggestion>) -> Result<String, String> {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+    let parts: Vec<String> = stem.split("-").map(|part| part.trim().to_lowercase()).collect();
+    if parts.len() != 6 {
+        return Err(format!("INVALID FILE NAME: receipt must consist of 6 distinct parts but found {}\n{}", parts.len(), file_name));
+    }
+
+    let mut date = normalize_date(&parts[0]);
+    if date.is_none() && suggestion.is_some() {
+        date = suggestion.unwrap().date.clone();
+    }
+    if date.is_none() {
+        return Err(format!("INVALID DATE: could not read '{}' as a date\n{}", parts[0], file_name));
+    }
+    let mut cost = normalize_cost(&parts[2]);
+    if cost.is_none() && suggestion.is_some() {
+        cost = suggestion.unwrap().total.map(|total| format!("{:.2}", total));
+    }
+    if cost.is_none() {
+        return Err(format!("INVALID COST: could not read '{}' as an amount with at most two decimals\n{}", parts[2], file_name));
+    }
+
+    let normalized = format!(
+        "{}-{}-{}-{}-{}-{}.{}",
+        date.unwrap(),
+        config.aliases.vendor(&parts[1]),
+        cost.unwrap(),
+        parts[3],
+        config.aliases.category(&parts[4]),
+        config.aliases.location(&parts[5]),
+        path.extension().and_then(|ext| ext.to_str()).unwrap_or("pdf").to_lowercase(),
+    );
+
+    // making sure the result passes the same rules as every other receipt
+    let full_path = format!("{}/{}", dir, normalized);
+    PdfLineItem::new(dir, &full_path)?;
+    return Ok(normalized);
+}
+
+/// accepts MMDDYY, MDDYY, MMDDYYYY, YYYYMMDD and '.', '_' or ' ' separated forms
+pub fn normalize_date(date: &str) -> Option<String> {
+    let date = date.trim();
+    let pieces: Vec<&str> = date.split(['.', '_', ' ', '/']).filter(|piece| !piece.is_empty()).collect();
+    let (month, day, year): (&str, &str, &str);
+    if pieces.len() == 3 {
+        if pieces[0].len() == 4 {
+            (year, month, day) = (pieces[0], pieces[1], pieces[2]);
+        } else {
+            (month, day, year) = (pieces[0], pieces[1], pieces[2]);
+        }
+    } else if pieces.len() == 1 && date.chars().all(|c| c.is_ascii_digit()) {
+        match date.len() {
+            5 => (month, day, year) = (&date[0..1], &date[1..3], &date[3..5]),
+            6 => (month, day, year) = (&date[0..2], &date[2..4], &date[4..6]),
+            8 if date.starts_with("20") || date.starts_with("19") => {
+                (year, month, day) = (&date[0..4], &date[4..6], &date[6..8])
+            }
+            8 => (month, day, year) = (&date[0..2], &date[2..4], &date[4..8]),
+            _ => return None,
+        }
+    } else {
+        return None;
+    }
+
+    let month = month.parse::<u32>().ok()?;
+    let day = day.parse::<u32>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    if year.len() != 2 && year.len() != 4 {
+        return None;
+    }
+    let year = year.parse::<u32>().ok()? % 100;
+    return Some(format!("{:02}{:02}{:02}", month, day, year));
+}
+
+/// strips currency noise and pads to exactly two decimal places
+pub fn normalize_cost(cost: &str) -> Option<String> {
+    let cost = cost.trim().trim_start_matches('$').replace(",", "");
+    let cost = Decimal::from_str(&cost).ok()?;
+    if cost.is_sign_negative() || cost.round_dp(2) != cost {
+        return None;
+    }
+    return Some(format!("{:.2}", cost));
+}
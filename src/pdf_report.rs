@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use genpdf::{elements, style, Alignment, Element};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::pdf_config::PdfConfig;
+use crate::pdf_csv::write_csv;
+use crate::pdf_invoice::{location_shares, new_document, PdfExpenseCategory, PdfLineItem, PdfReadOptions, PdfSkippedFile};
+use crate::pdf_period::{PdfPeriod, PdfPeriodLength};
+
+/// what a comparison report breaks the totals down by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PdfReportGroup {
+    Category,
+    Location,
+}
+
+impl FromStr for PdfReportGroup {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<PdfReportGroup, String> {
+        match value {
+            "category" => Ok(PdfReportGroup::Category),
+            "location" => Ok(PdfReportGroup::Location),
+            _ => Err(format!("INVALID GROUP: reports group by 'category' or 'location', not '{}'", value)),
+        }
+    }
+}
+
+impl fmt::Display for PdfReportGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PdfReportGroup::Category => write!(f, "category"),
+            PdfReportGroup::Location => write!(f, "location"),
+        }
+    }
+}
+
+/// an earlier total and how far the current total moved from it
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfChange {
+    pub total: Decimal,
+    pub change: Decimal,
+    pub percent_change: Option<Decimal>,
+}
+
+impl PdfChange {
+
+    /// there is no percent change from nothing
+    pub fn new(current: Decimal, mut total: Decimal) -> PdfChange {
+        total.rescale(2);
+        let mut change = current - total;
+        change.rescale(2);
+        let percent_change = Some(total).filter(|total| !total.is_zero()).map(|total| {
+            let mut percent = (change * Decimal::ONE_HUNDRED / total).round_dp(1);
+            percent.rescale(1);
+            percent
+        });
+        return PdfChange {
+            total: total,
+            change: change,
+            percent_change: percent_change,
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        let percent = match self.percent_change {
+            Some(percent) => format!("{:+}%", percent),
+            None if self.change.is_zero() => "-".to_owned(),
+            None => "new".to_owned(),
+        };
+        return format!("{:+}, {}", self.change, percent);
+    }
+
+}
+
+/// one category or location across the three periods
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfComparisonLine {
+    pub name: String,
+    pub current: Decimal,
+    pub previous: PdfChange,
+    pub year_earlier: PdfChange,
+}
+
+impl PdfComparisonLine {
+
+    pub fn new(name: &str, mut current: Decimal, previous: Decimal, year_earlier: Decimal) -> PdfComparisonLine {
+        current.rescale(2);
+        return PdfComparisonLine {
+            name: name.to_owned(),
+            current: current,
+            previous: PdfChange::new(current, previous),
+            year_earlier: PdfChange::new(current, year_earlier),
+        };
+    }
+
+}
+
+/// totals for a period side by side with the period before it and the same period a year earlier
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfComparisonReport {
+    pub group: String,
+    pub period: String,
+    pub previous_period: String,
+    pub year_earlier_period: String,
+    pub lines: Vec<PdfComparisonLine>,
+    pub total: PdfComparisonLine,
+    pub skipped: Vec<PdfSkippedFile>,
+}
+
+impl PdfComparisonReport {
+
+    /// without a period the month of the latest receipt is used. by location, split receipts
+    /// are shared out the same way `sort` shares them
+    pub fn new(dir: &str, period: Option<PdfPeriod>, group: PdfReportGroup, read: &PdfReadOptions, config: &PdfConfig) -> Result<PdfComparisonReport, String> {
+        let (line_items, skipped) = PdfLineItem::new_from_dir(dir, read)?;
+        let period = match period {
+            Some(period) => period,
+            None => {
+                let dates: Vec<&str> = line_items.iter().map(|item| item.date.as_str()).collect();
+                let latest = PdfPeriod::latest(&dates, PdfPeriodLength::Month);
+                if latest.is_none() {
+                    return Err(format!("EMPTY DIR: {} has no receipts to pick a period from, pass --period", dir));
+                }
+                latest.unwrap()
+            },
+        };
+        let previous_period = period.previous();
+        let year_earlier_period = period.year_earlier();
+
+        let all_shares = location_shares(&line_items, &config.split)?;
+        let current = PdfComparisonReport::totals(&line_items, &all_shares, &period, group);
+        let previous = PdfComparisonReport::totals(&line_items, &all_shares, &previous_period, group);
+        let year_earlier = PdfComparisonReport::totals(&line_items, &all_shares, &year_earlier_period, group);
+
+        let mut names: Vec<String> = current.keys().chain(previous.keys()).chain(year_earlier.keys()).cloned().collect();
+        names.sort();
+        names.dedup();
+        let total_of = |totals: &BTreeMap<String, Decimal>, name: &str| totals.get(name).copied().unwrap_or(Decimal::ZERO);
+        let lines: Vec<PdfComparisonLine> = names
+            .iter()
+            .map(|name| PdfComparisonLine::new(name, total_of(&current, name), total_of(&previous, name), total_of(&year_earlier, name)))
+            .collect();
+        let total = PdfComparisonLine::new("total", current.values().sum(), previous.values().sum(), year_earlier.values().sum());
+
+        let report = PdfComparisonReport {
+            group: group.to_string(),
+            period: period.to_string(),
+            previous_period: previous_period.to_string(),
+            year_earlier_period: year_earlier_period.to_string(),
+            lines: lines,
+            total: total,
+            skipped: skipped,
+        };
+        return Ok(report);
+    }
+
+    // what each category or location spent in one period
+    fn totals(line_items: &[PdfLineItem], all_shares: &[Vec<(String, Decimal)>], period: &PdfPeriod, group: PdfReportGroup) -> BTreeMap<String, Decimal> {
+        let mut totals: BTreeMap<String, Decimal> = BTreeMap::new();
+        let in_period = line_items.iter().zip(all_shares).filter(|(item, _)| period.contains(&item.date));
+        match group {
+            PdfReportGroup::Category => {
+                let items: Vec<PdfLineItem> = in_period
+                    .map(|(item, _)| {
+                        let mut item = item.clone();
+                        item.category = item.category.to_lowercase();
+                        item
+                    })
+                    .collect();
+                for category in PdfExpenseCategory::new_from_line_items(items) {
+                    totals.insert(category.name, category.total_cost);
+                }
+            },
+            PdfReportGroup::Location => {
+                for (location, cost) in in_period.flat_map(|(_, shares)| shares) {
+                    *totals.entry(location.clone()).or_insert(Decimal::ZERO) += cost;
+                }
+            },
+        }
+        return totals;
+    }
+
+    pub fn describe(&self, line: &PdfComparisonLine) -> String {
+        return format!(
+            "{}: {} | {}: {} ({}) | {}: {} ({})",
+            line.name,
+            line.current,
+            self.previous_period,
+            line.previous.total,
+            line.previous.describe(),
+            self.year_earlier_period,
+            line.year_earlier.total,
+            line.year_earlier.describe(),
+        );
+    }
+
+    /// one row per category or location, then a `total` row
+    pub fn write_csv(&self, path: &str) -> Result<(), String> {
+        let header = [
+            self.group.as_str(),
+            "period",
+            "total",
+            "previous_period",
+            "previous_total",
+            "previous_change",
+            "previous_percent_change",
+            "year_earlier_period",
+            "year_earlier_total",
+            "year_earlier_change",
+            "year_earlier_percent_change",
+        ];
+        let mut rows: Vec<Vec<String>> = vec![];
+        for line in self.lines.iter().chain([&self.total]) {
+            rows.push(vec![
+                line.name.clone(),
+                self.period.clone(),
+                line.current.to_string(),
+                self.previous_period.clone(),
+                line.previous.total.to_string(),
+                line.previous.change.to_string(),
+                line.previous.percent_change.map(|percent| percent.to_string()).unwrap_or_default(),
+                self.year_earlier_period.clone(),
+                line.year_earlier.total.to_string(),
+                line.year_earlier.change.to_string(),
+                line.year_earlier.percent_change.map(|percent| percent.to_string()).unwrap_or_default(),
+            ]);
+        }
+        return write_csv(path, &header, &rows);
+    }
+
+    pub fn write_json(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self);
+        if json.is_err() {
+            println!("{:?}", json.err().unwrap()); // third-party error
+            return Err("JSON FAILURE: failed to serialize the report".to_owned());
+        }
+        let result = fs::write(path, json.unwrap() + "\n");
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("FILE WRITE FAILURE: failed to write {}", path));
+        }
+        return Ok(());
+    }
+
+    pub fn write_pdf(&self, path: &str) -> Result<(), String> {
+        let title = format!("{} by {}", self.period, self.group);
+        let mut doc = new_document(&title);
+        doc.push(elements::Paragraph::new(&title).aligned(Alignment::Left).styled(style::Style::new().bold().with_font_size(20)));
+        doc.push(elements::Break::new(1));
+
+        let mut table = elements::TableLayout::new(vec![3, 2, 2, 3, 2, 3]);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+        let header = [self.group.clone(), self.period.clone(), self.previous_period.clone(), "change".to_owned(), self.year_earlier_period.clone(), "change".to_owned()];
+        let mut row = table.row();
+        for cell in header {
+            row.push_element(elements::Paragraph::new(cell).styled(style::Style::new().bold()).padded(1));
+        }
+        let result = row.push();
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to lay out the report table: {}", path));
+        }
+        for (i, line) in self.lines.iter().chain([&self.total]).enumerate() {
+            let mut row = table.row();
+            let cells = [
+                line.name.clone(),
+                line.current.to_string(),
+                line.previous.total.to_string(),
+                line.previous.describe(),
+                line.year_earlier.total.to_string(),
+                line.year_earlier.describe(),
+            ];
+            for cell in cells {
+                let mut cell_style = style::Style::new();
+                if i == self.lines.len() {
+                    cell_style = cell_style.bold();
+                }
+                row.push_element(elements::Paragraph::new(cell).styled(cell_style).padded(1));
+            }
+            let pushed = row.push();
+            if pushed.is_err() {
+                println!("{:?}", pushed.err().unwrap()); // third-party error
+                return Err(format!("PDF RENDER FAILURE: failed to lay out the report table: {}", path));
+            }
+        }
+        doc.push(table);
+
+        let output_file = doc.render_to_file(path);
+        if output_file.is_err() {
+            println!("{:?}", output_file.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", path));
+        }
+        return Ok(());
+    }
+
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use genpdf::{elements, style, Alignment, Element};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::pdf_allocate::{PdfSplitOptions, SPLIT_LOCATIONS};
+use crate::pdf_config::PdfConfig;
+use crate::pdf_csv::write_csv;
+use crate::pdf_duplicates::{day_number, PdfDuplicateOptions, PdfDuplicateReport};
+use crate::pdf_invoice::{location_shares, new_document, PdfLineItem, PdfReadOptions, PdfSkippedFile};
+
+/// the `[settle]` section of the config, which location pays each card's bill
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PdfSettleConfig {
+    #[serde(default)]
+    pub cards: BTreeMap<String, String>,
+    pub default_card: Option<String>,
+}
+
+impl PdfSettleConfig {
+
+    /// who paid for a receipt, a `paid_by` sidecar field wins over the card it went on
+    pub fn payer(&self, item: &PdfLineItem) -> Result<Option<String>, String> {
+        if item.paid_by.is_some() {
+            return Ok(item.paid_by.clone());
+        }
+        let card = item.card.clone().or(self.default_card.clone());
+        if card.is_none() {
+            return Ok(None);
+        }
+        let card = card.unwrap().to_lowercase();
+        let payer = self.cards.iter().find(|(name, _)| name.to_lowercase() == card).map(|(_, payer)| payer.to_lowercase());
+        if payer.is_none() {
+            return Err(format!("UNKNOWN CARD: '{}' has no paying location under [settle.cards] in the config\n{}", card, item.path));
+        }
+        let payer = payer.unwrap();
+        if !SPLIT_LOCATIONS.contains(&payer.as_str()) {
+            return Err(format!("INVALID PAYER: card '{}' must be paid by one of {}, not '{}'", card, SPLIT_LOCATIONS.join(", "), payer));
+        }
+        return Ok(Some(payer));
+    }
+
+}
+
+/// the first and last day of a settlement, both MMDDYY and both included
+#[derive(Debug, Clone, Default)]
+pub struct PdfSettlePeriod {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl PdfSettlePeriod {
+
+    pub fn contains(&self, date: &str) -> Result<bool, String> {
+        let day = day_number(date);
+        if day.is_none() {
+            return Err(format!("INVALID DATE: '{}' is not a MMDDYY date", date));
+        }
+        let from = self.from.as_deref().and_then(day_number);
+        let to = self.to.as_deref().and_then(day_number);
+        if (from.is_some() && day < from) || (to.is_some() && day > to) {
+            return Ok(false);
+        }
+        return Ok(true);
+    }
+
+    pub fn describe(&self) -> String {
+        return match (&self.from, &self.to) {
+            (Some(from), Some(to)) => format!("{} to {}", from, to),
+            (Some(from), None) => format!("from {}", from),
+            (None, Some(to)) => format!("up to {}", to),
+            (None, None) => "all receipts".to_owned(),
+        };
+    }
+
+}
+
+/// a receipt one location paid for that another location owes a share of
+#[derive(Debug, Clone)]
+pub struct PdfSettlementReceipt {
+    pub path: String,
+    pub date: String,
+    pub vendor: String,
+    pub description: String,
+    pub cost: Decimal,
+    pub paid_by: String,
+    pub owed: Vec<(String, Decimal)>,
+}
+
+/// what one location owes another once everything is netted out
+#[derive(Debug, Clone)]
+pub struct PdfSettlementBalance {
+    pub debtor: String,
+    pub creditor: String,
+    pub amount: Decimal,
+}
+
+/// who owes whom for the shared expenses of a period
+#[derive(Debug, Clone)]
+pub struct PdfSettlement {
+    pub period: PdfSettlePeriod,
+    pub split: PdfSplitOptions,
+    pub receipts: Vec<PdfSettlementReceipt>,
+    pub balances: Vec<PdfSettlementBalance>,
+    pub unknown_payer: Vec<String>,
+    pub skipped: Vec<PdfSkippedFile>,
+    pub excluded: Vec<String>,
+}
+
+impl PdfSettlement {
+
+    /// each receipt in the period is shared out like `sort` shares it, and every share a
+    /// location other than the payer takes is owed to the payer. receipts without a payer
+    /// are taken to be paid by their own location, split ones are listed as unknown
+    pub fn new(dir: &str, period: &PdfSettlePeriod, read: &PdfReadOptions, duplicates: &PdfDuplicateOptions, config: &PdfConfig) -> Result<PdfSettlement, String> {
+        for bound in [&period.from, &period.to].into_iter().flatten() {
+            if day_number(bound).is_none() {
+                return Err(format!("INVALID DATE: the period bound '{}' should be a MMDDYY date like '010125'", bound));
+            }
+        }
+        let (mut line_items, skipped) = PdfLineItem::new_from_dir(dir, read)?;
+
+        // confirmed duplicates are left out before any turns are counted, as the sort leaves them out
+        let mut excluded: Vec<String> = vec![];
+        if duplicates.exclude {
+            excluded = PdfDuplicateReport::new(&line_items, duplicates.days)?.confirmed();
+            line_items.retain(|item| !excluded.contains(&item.path));
+        }
+        let mut settlement = PdfSettlement {
+            period: period.clone(),
+            split: config.split.clone(),
+            receipts: vec![],
+            balances: vec![],
+            unknown_payer: vec![],
+            skipped: skipped,
+            excluded: excluded,
+        };
+
+        // shared out over the whole dir so a rotating remainder matches the sort
+        let all_shares = location_shares(&line_items, &config.split)?;
+        let mut owed: BTreeMap<(String, String), Decimal> = BTreeMap::new();
+        for (item, shares) in line_items.iter().zip(all_shares) {
+            let location = item.location_name();
+            if !period.contains(&item.date)? {
+                continue;
+            }
+
+            let payer = config.settle.payer(item)?;
+            if payer.is_none() {
+                if location == "split" {
+                    settlement.unknown_payer.push(item.path.clone());
+                }
+                continue;
+            }
+            let payer = payer.unwrap();
+            let shares: Vec<(String, Decimal)> = shares.into_iter().filter(|(location, cost)| *location != payer && !cost.is_zero()).collect();
+            if shares.is_empty() {
+                continue;
+            }
+            for (location, cost) in &shares {
+                *owed.entry((location.clone(), payer.clone())).or_insert(Decimal::ZERO) += cost;
+            }
+            settlement.receipts.push(PdfSettlementReceipt {
+                path: item.path.clone(),
+                date: item.date.clone(),
+                vendor: item.vendor.clone(),
+                description: item.description.clone(),
+                cost: item.cost,
+                paid_by: payer,
+                owed: shares,
+            });
+        }
+
+        // netting each pair of locations against each other
+        for (i, a) in SPLIT_LOCATIONS.iter().enumerate() {
+            for b in SPLIT_LOCATIONS.iter().skip(i + 1) {
+                let a_owes = owed.get(&(a.to_string(), b.to_string())).copied().unwrap_or(Decimal::ZERO);
+                let b_owes = owed.get(&(b.to_string(), a.to_string())).copied().unwrap_or(Decimal::ZERO);
+                let net = a_owes - b_owes;
+                if net.is_zero() {
+                    continue;
+                }
+                let (debtor, creditor) = if net.is_sign_positive() { (a, b) } else { (b, a) };
+                settlement.balances.push(PdfSettlementBalance {
+                    debtor: debtor.to_string(),
+                    creditor: creditor.to_string(),
+                    amount: net.abs(),
+                });
+            }
+        }
+        return Ok(settlement);
+    }
+
+    /// one row per share owed, then a `net` row per balance
+    pub fn write_csv(&self, path: &str) -> Result<(), String> {
+        let header = ["file", "date", "vendor", "cost", "paid_by", "owed_by", "owed"];
+        let mut rows: Vec<Vec<String>> = vec![];
+        for receipt in &self.receipts {
+            for (location, cost) in &receipt.owed {
+                rows.push(vec![
+                    receipt.path.clone(),
+                    receipt.date.clone(),
+                    receipt.vendor.clone(),
+                    receipt.cost.to_string(),
+                    receipt.paid_by.clone(),
+                    location.clone(),
+                    format!("{:.2}", cost),
+                ]);
+            }
+        }
+        for balance in &self.balances {
+            rows.push(vec![
+                "net".to_owned(),
+                String::new(),
+                String::new(),
+                String::new(),
+                balance.creditor.clone(),
+                balance.debtor.clone(),
+                format!("{:.2}", balance.amount),
+            ]);
+        }
+        return write_csv(path, &header, &rows);
+    }
+
+    pub fn write_pdf(&self, path: &str) -> Result<(), String> {
+        let title = format!("settlement: {}", self.period.describe());
+        let mut doc = new_document(&title);
+        doc.push(elements::Paragraph::new(&title).aligned(Alignment::Left).styled(style::Style::new().bold().with_font_size(20)));
+        doc.push(elements::Break::new(1));
+
+        if self.balances.is_empty() {
+            doc.push(elements::Paragraph::new("nothing is owed between locations"));
+        }
+        for balance in &self.balances {
+            let line = format!("{} owes {} {:.2}", balance.debtor, balance.creditor, balance.amount);
+            doc.push(elements::Paragraph::new(line).styled(style::Style::new().bold().with_font_size(16)));
+        }
+        doc.push(elements::Paragraph::new(format!("splits shared with {}", self.split)));
+        doc.push(elements::Break::new(1));
+
+        doc.push(elements::Paragraph::new("contributing receipts").styled(style::Style::new().bold().with_font_size(16)));
+        for receipt in &self.receipts {
+            let owed: Vec<String> = receipt.owed.iter().map(|(location, cost)| format!("{} owes {:.2}", location, cost)).collect();
+            let line = format!("[{}] [{}] [{}] [{}] paid by {}: {}", receipt.date, receipt.description, receipt.vendor, receipt.cost, receipt.paid_by, owed.join(", "));
+            doc.push(elements::Paragraph::new(line));
+        }
+
+        if !self.unknown_payer.is_empty() {
+            doc.push(elements::Break::new(1));
+            doc.push(elements::Paragraph::new("split receipts with no known payer").styled(style::Style::new().bold().with_font_size(16)));
+            for path in &self.unknown_payer {
+                doc.push(elements::Paragraph::new(path.as_str()));
+            }
+        }
+
+        if !self.excluded.is_empty() {
+            doc.push(elements::Break::new(1));
+            doc.push(elements::Paragraph::new("excluded duplicates").styled(style::Style::new().bold().with_font_size(16)));
+            for path in &self.excluded {
+                doc.push(elements::Paragraph::new(path.as_str()));
+            }
+        }
+
+        let output_file = doc.render_to_file(path);
+        if output_file.is_err() {
+            println!("{:?}", output_file.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", path));
+        }
+        return Ok(());
+    }
+
+}
@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde_json::{Map, Value};
+
+use crate::pdf_allocate::SPLIT_LOCATIONS;
+use crate::pdf_invoice::{is_valid_location, PdfLineItem};
+
+/// the formats a sidecar can be written in
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfSidecarFormat {
+    Toml,
+    Json,
+}
+
+impl PdfSidecarFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PdfSidecarFormat::Toml => "toml",
+            PdfSidecarFormat::Json => "json",
+        }
+    }
+
+    pub fn from_extension(ext: &str) -> Option<PdfSidecarFormat> {
+        match ext {
+            "toml" => Some(PdfSidecarFormat::Toml),
+            "json" => Some(PdfSidecarFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// a `<receipt>.toml` or `<receipt>.json` file sitting next to a receipt
+/// holding details that do not fit in the file name
+#[derive(Debug, Clone)]
+pub struct PdfSidecar {
+    pub path: String,
+    pub format: PdfSidecarFormat,
+    pub values: Map<String, Value>,
+}
+
+impl PdfSidecar {
+
+    pub fn new(path: &str) -> Result<PdfSidecar, String> {
+
+        // working out the format from the extension
+        let ext = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let format = PdfSidecarFormat::from_extension(ext);
+        if format.is_none() {
+            return Err(format!("INVALID SIDECAR: sidecar files must end in .toml or .json\n{}", path));
+        }
+        let format = format.unwrap();
+
+        // reading the file
+        let contents = fs::read_to_string(path);
+        if contents.is_err() {
+            println!("{:?}", contents.err().unwrap()); // third-party error
+            return Err(format!("SIDECAR READ FAILURE: failed to read sidecar file\n{}", path));
+        }
+        let contents = contents.unwrap();
+
+        // parsing into a single key/value table regardless of the format
+        let parsed: Result<Value, String> = match format {
+            PdfSidecarFormat::Toml => toml::from_str::<Value>(&contents).map_err(|err| err.to_string()),
+            PdfSidecarFormat::Json => serde_json::from_str::<Value>(&contents).map_err(|err| err.to_string()),
+        };
+        if parsed.is_err() {
+            println!("{}", parsed.err().unwrap()); // third-party error
+            return Err(format!("INVALID SIDECAR: failed to parse sidecar file\n{}", path));
+        }
+        let values = match parsed.unwrap() {
+            Value::Object(values) => values,
+            _ => return Err(format!("INVALID SIDECAR: sidecar file must contain a table of fields\n{}", path)),
+        };
+
+        let sidecar = PdfSidecar {
+            path: path.to_owned(),
+            format: format,
+            values: values,
+        };
+        return Ok(sidecar);
+    }
+
+    /// looks for `<receipt>.toml` then `<receipt>.json` next to the receipt
+    pub fn new_for_receipt(receipt_path: &str) -> Result<Option<PdfSidecar>, String> {
+        let toml_path = PdfSidecar::path_for_receipt(receipt_path, &PdfSidecarFormat::Toml);
+        let json_path = PdfSidecar::path_for_receipt(receipt_path, &PdfSidecarFormat::Json);
+        let has_toml = Path::new(&toml_path).is_file();
+        let has_json = Path::new(&json_path).is_file();
+        if has_toml && has_json {
+            return Err(format!("AMBIGUOUS SIDECAR: a receipt may only have one sidecar but found both .toml and .json\n{}", receipt_path));
+        }
+        if has_toml {
+            return Ok(Some(PdfSidecar::new(&toml_path)?));
+        }
+        if has_json {
+            return Ok(Some(PdfSidecar::new(&json_path)?));
+        }
+        return Ok(None);
+    }
+
+    pub fn path_for_receipt(receipt_path: &str, format: &PdfSidecarFormat) -> String {
+        let path = Path::new(receipt_path).with_extension(format.extension());
+        return path.to_string_lossy().to_string();
+    }
+
+    /// where this sidecar belongs for another receipt
+    pub fn path_for(&self, receipt_path: &str) -> String {
+        return PdfSidecar::path_for_receipt(receipt_path, &self.format);
+    }
+
+    pub fn is_sidecar_path(path: &Path) -> bool {
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        return PdfSidecarFormat::from_extension(ext).is_some();
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        let value = self.values.get(key)?;
+        return match value {
+            Value::String(value) => Some(value.trim().to_owned()),
+            Value::Number(value) => Some(value.to_string()),
+            Value::Bool(value) => Some(value.to_string()),
+            _ => None,
+        };
+    }
+
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        let value = self.values.get(key);
+        return match value {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::String(value) => Some(value.trim().to_owned()),
+                    Value::Number(value) => Some(value.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            Some(Value::String(value)) => value
+                .split(",")
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+                .collect(),
+            _ => vec![],
+        };
+    }
+
+    /// merges the sidecar into the line item, sidecar values win over the file name
+    /// the path is left alone so the line item still points at the file on disk
+    pub fn apply(&self, item: &mut PdfLineItem) -> Option<String> {
+
+        if let Some(date) = self.get_str("date") {
+            if date.len() != 6 || !date.chars().all(|c| c.is_ascii_digit()) {
+                return Some(format!("INVALID SIDECAR DATE: sidecar 'date' field should only consist of 6 digits like '010125'\n{}", self.path));
+            }
+            let month = date[0..2].parse::<u32>().unwrap(); // only digits
+            let day = date[2..4].parse::<u32>().unwrap(); // only digits
+            if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+                return Some(format!("INVALID SIDECAR DATE: sidecar 'date' field should be a real MMDDYY date like '010125'\n{}", self.path));
+            }
+            item.date = date;
+        }
+
+        if let Some(vendor) = self.get_str("vendor") {
+            item.vendor = vendor;
+        }
+
+        if let Some(cost) = self.get_str("cost") {
+            let cost = Decimal::from_str(&cost);
+            if cost.is_err() {
+                println!("{:?}", cost.err().unwrap()); // third-party error
+                return Some(format!("INVALID SIDECAR COST: sidecar 'cost' field failed to convert into a Decimal\n{}", self.path));
+            }
+            item.cost = cost.unwrap();
+        }
+
+        if let Some(description) = self.get_str("description") {
+            item.description = description;
+        }
+
+        if let Some(category) = self.get_str("category") {
+            item.category = category;
+        }
+
+        if let Some(location) = self.get_str("location") {
+            let mut location = location.to_lowercase();
+            if !location.contains(".") {
+                location = format!("{}.{}", location, item.extension());
+            }
+            if !is_valid_location(&location) {
+                return Some(format!("INVALID SIDECAR LOCATION: sidecar 'location' field must be 'southroads', 'utica', or 'split'\n{}", self.path));
+            }
+            item.location = location;
+        }
+
+        if let Some(notes) = self.get_str("notes") {
+            item.notes = Some(notes);
+        }
+
+        let attendees = self.get_list("attendees");
+        if !attendees.is_empty() {
+            item.attendees = attendees;
+        }
+
+        if let Some(mileage) = self.get_str("mileage") {
+            let mileage = Decimal::from_str(&mileage);
+            if mileage.is_err() {
+                println!("{:?}", mileage.err().unwrap()); // third-party error
+                return Some(format!("INVALID SIDECAR MILEAGE: sidecar 'mileage' field failed to convert into a Decimal\n{}", self.path));
+            }
+            item.mileage = Some(mileage.unwrap());
+        }
+
+        if let Some(approval_status) = self.get_str("approval_status") {
+            item.approval_status = Some(approval_status);
+        }
+
+        if let Some(paid_by) = self.get_str("paid_by") {
+            let paid_by = paid_by.to_lowercase();
+            if !SPLIT_LOCATIONS.contains(&paid_by.as_str()) {
+                return Some(format!("INVALID SIDECAR PAYER: sidecar 'paid_by' field must be one of {}\n{}", SPLIT_LOCATIONS.join(", "), self.path));
+            }
+            item.paid_by = Some(paid_by);
+        }
+
+        if let Some(card) = self.get_str("card") {
+            item.card = Some(card.to_lowercase());
+        }
+
+        if let Some(submitter) = self.get_str("submitter").filter(|submitter| !submitter.is_empty()) {
+            item.submitter = Some(submitter.to_lowercase());
+        }
+
+        item.sidecar = Some(self.clone());
+        return None;
+    }
+
+    /// writes a copy of this sidecar next to another receipt, replacing the given fields
+    pub fn write_for_receipt(&self, receipt_path: &str, overrides: &[(&str, String)]) -> Result<String, String> {
+        let mut values = self.values.clone();
+        for (key, value) in overrides {
+            values.insert(key.to_string(), Value::String(value.clone()));
+        }
+        let values = Value::Object(values);
+        let contents: Result<String, String> = match self.format {
+            PdfSidecarFormat::Toml => toml::to_string(&values).map_err(|err| err.to_string()),
+            PdfSidecarFormat::Json => serde_json::to_string_pretty(&values).map_err(|err| err.to_string()),
+        };
+        if contents.is_err() {
+            println!("{}", contents.err().unwrap()); // third-party error
+            return Err(format!("SIDECAR WRITE FAILURE: failed to serialize sidecar\n{}", self.path));
+        }
+        let out_path = PdfSidecar::path_for_receipt(receipt_path, &self.format);
+        let result = fs::write(&out_path, contents.unwrap());
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("SIDECAR WRITE FAILURE: failed to write sidecar to {}", out_path));
+        }
+        return Ok(out_path);
+    }
+
+}
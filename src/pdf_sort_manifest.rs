@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pdf_sort_plan::PdfSortAllocation;
+
+pub const MANIFEST_FILE: &str = "finli-manifest.json";
+
+/// what a previous sort did with one receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfSortManifestEntry {
+    pub source: String,
+    pub hash: String,
+    pub sidecar_hash: Option<String>,
+    pub destinations: Vec<String>,
+    pub allocations: Vec<PdfSortAllocation>,
+    pub moved: bool,
+    pub sorted_at: u64,
+}
+
+impl PdfSortManifestEntry {
+
+    /// the receipt on disk is the one this entry was written from
+    pub fn matches(&self, hash: &str, sidecar_hash: &Option<String>) -> bool {
+        return !self.moved && self.hash == hash && &self.sidecar_hash == sidecar_hash;
+    }
+
+}
+
+/// the record kept in the output dir so later sorts only handle new or changed receipts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PdfSortManifest {
+    pub entries: Vec<PdfSortManifestEntry>,
+}
+
+impl PdfSortManifest {
+
+    pub fn path_for(out: &str) -> String {
+        return Path::new(out).join(MANIFEST_FILE).to_string_lossy().to_string();
+    }
+
+    /// an output dir that has never been sorted into has an empty manifest
+    pub fn new_from_dir(out: &str) -> Result<PdfSortManifest, String> {
+        let path = PdfSortManifest::path_for(out);
+        if !Path::new(&path).is_file() {
+            return Ok(PdfSortManifest::default());
+        }
+        let contents = fs::read_to_string(&path);
+        if contents.is_err() {
+            println!("{:?}", contents.err().unwrap()); // third-party error
+            return Err(format!("MANIFEST READ FAILURE: failed to read the sort manifest\n{}", path));
+        }
+        let manifest = serde_json::from_str::<PdfSortManifest>(&contents.unwrap());
+        if manifest.is_err() {
+            println!("{}", manifest.err().unwrap()); // third-party error
+            return Err(format!("INVALID MANIFEST: failed to parse the sort manifest, remove it or sort with --full\n{}", path));
+        }
+        return Ok(manifest.unwrap());
+    }
+
+    pub fn is_manifest_path(path: &Path) -> bool {
+        return path.file_name().and_then(|name| name.to_str()) == Some(MANIFEST_FILE);
+    }
+
+    pub fn get(&self, source: &str) -> Option<&PdfSortManifestEntry> {
+        return self.entries.iter().find(|entry| entry.source == source);
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self);
+        if json.is_err() {
+            println!("{}", json.err().unwrap()); // third-party error
+            return Err("JSON FAILURE: failed to serialize the sort manifest".to_owned());
+        }
+        let result = fs::write(path, json.unwrap());
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("MANIFEST WRITE FAILURE: failed to write the sort manifest to {}", path));
+        }
+        return Ok(());
+    }
+
+}
@@ -0,0 +1,646 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::pdf_allocate::{split_cost, PdfSplitOptions};
+use crate::pdf_duplicates::{PdfDuplicateOptions, PdfDuplicateReport};
+use crate::pdf_hash::hash_file;
+use crate::pdf_image::is_image_path;
+use crate::pdf_invoice::{PdfLineItem, PdfReadOptions, PdfSkippedFile};
+use crate::pdf_sort_manifest::{PdfSortManifest, PdfSortManifestEntry};
+
+/// how each receipt gets from the inbox into the sorted output
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfSortMode {
+    Copy,
+    Move,
+    Hardlink,
+    Reflink,
+    Symlink,
+}
+
+impl FromStr for PdfSortMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<PdfSortMode, String> {
+        return match mode {
+            "copy" => Ok(PdfSortMode::Copy),
+            "move" => Ok(PdfSortMode::Move),
+            "hardlink" => Ok(PdfSortMode::Hardlink),
+            "reflink" => Ok(PdfSortMode::Reflink),
+            "symlink" => Ok(PdfSortMode::Symlink),
+            _ => Err(format!("INVALID SORT MODE: '{}' must be one of copy, move, hardlink, reflink or symlink", mode)),
+        };
+    }
+}
+
+/// what to do when a destination is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfConflictPolicy {
+    Skip,
+    Overwrite,
+    Rename,
+    Fail,
+}
+
+impl FromStr for PdfConflictPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<PdfConflictPolicy, String> {
+        return match policy {
+            "skip" => Ok(PdfConflictPolicy::Skip),
+            "overwrite" => Ok(PdfConflictPolicy::Overwrite),
+            "rename" => Ok(PdfConflictPolicy::Rename),
+            "fail" => Ok(PdfConflictPolicy::Fail),
+            _ => Err(format!("INVALID CONFLICT POLICY: '{}' must be one of skip, overwrite, rename or fail", policy)),
+        };
+    }
+}
+
+/// everything that changes how a sort behaves
+#[derive(Debug, Clone)]
+pub struct PdfSortOptions {
+    pub mode: PdfSortMode,
+    pub on_conflict: PdfConflictPolicy,
+    pub full: bool,
+    pub layout: String,
+    pub convert_images: bool,
+    pub split: PdfSplitOptions,
+    pub read: PdfReadOptions,
+    pub duplicates: PdfDuplicateOptions,
+}
+
+impl Default for PdfSortOptions {
+    fn default() -> PdfSortOptions {
+        return PdfSortOptions {
+            mode: PdfSortMode::Copy,
+            on_conflict: PdfConflictPolicy::Fail,
+            full: false,
+            layout: DEFAULT_LAYOUT.to_owned(),
+            convert_images: false,
+            split: PdfSplitOptions::default(),
+            read: PdfReadOptions::default(),
+            duplicates: PdfDuplicateOptions::default(),
+        };
+    }
+}
+
+/// a destination that was already taken and how it was dealt with
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfSortConflict {
+    pub source: String,
+    pub destination: String,
+    pub location: String,
+    pub cost: Decimal,
+    pub reason: String,
+    pub resolution: String,
+    pub skipped: bool,
+}
+
+/// a single file to be written by the sort
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfSortAction {
+    pub source: String,
+    pub destination: String,
+    pub location: String,
+    pub cost: Decimal,
+    pub split: bool,
+    pub convert: bool,
+    pub sidecar_source: Option<String>,
+    pub sidecar_destination: Option<String>,
+}
+
+/// a split receipt and the share each location takes
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfSortSplit {
+    pub source: String,
+    pub cost: Decimal,
+    pub allocations: Vec<PdfSortAllocation>,
+}
+
+/// the share of a split receipt one location takes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfSortAllocation {
+    pub location: String,
+    pub cost: Decimal,
+}
+
+/// everything a sort will do, worked out up front without touching disk
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfSortPlan {
+    pub source_dir: String,
+    pub out_dir: String,
+    pub mode: PdfSortMode,
+    pub on_conflict: PdfConflictPolicy,
+    pub layout: String,
+    pub split: PdfSplitOptions,
+    pub dirs: Vec<String>,
+    pub actions: Vec<PdfSortAction>,
+    pub splits: Vec<PdfSortSplit>,
+    pub conflicts: Vec<PdfSortConflict>,
+    pub input_total: Decimal,
+    pub unchanged: Vec<String>,
+    pub removed: Vec<String>,
+    pub invalid: Vec<PdfSkippedFile>,
+    pub duplicates: PdfDuplicateReport,
+    pub excluded: Vec<String>,
+    /// when the plan was made, in seconds since the epoch
+    pub sorted_at: u64,
+    #[serde(skip)]
+    pub manifest: PdfSortManifest,
+    #[serde(skip)]
+    already_sorted: Vec<(String, String)>,
+}
+
+impl PdfSortPlan {
+
+    /// reads every receipt in `dir` and plans sorting them into `out`
+    pub fn new(dir: &str, out: &str, options: &PdfSortOptions) -> Result<PdfSortPlan, String> {
+
+        // getting the line items
+        let (mut line_items, invalid) = PdfLineItem::new_from_dir(dir, &options.read)?;
+
+        // extra copies of the same scan are flagged, and left behind when asked
+        let duplicates = PdfDuplicateReport::new(&line_items, options.duplicates.days)?;
+        let mut excluded: Vec<String> = vec![];
+        if options.duplicates.exclude {
+            excluded = duplicates.confirmed();
+            line_items.retain(|item| !excluded.contains(&item.path));
+        }
+
+        let mut plan = PdfSortPlan::new_from_line_items(dir, out, line_items, options)?;
+        plan.invalid = invalid;
+        plan.duplicates = duplicates;
+        plan.excluded = excluded;
+        return Ok(plan);
+    }
+
+    /// plans the sort of line items already read from `dir`
+    pub fn new_from_line_items(dir: &str, out: &str, line_items: Vec<PdfLineItem>, options: &PdfSortOptions) -> Result<PdfSortPlan, String> {
+
+        validate_layout(&options.layout)?;
+        let mut plan = PdfSortPlan {
+            source_dir: dir.to_owned(),
+            out_dir: out.to_owned(),
+            mode: options.mode,
+            on_conflict: options.on_conflict,
+            layout: options.layout.clone(),
+            split: options.split.clone(),
+            dirs: vec![],
+            actions: vec![],
+            splits: vec![],
+            conflicts: vec![],
+            input_total: Decimal::ZERO,
+            unchanged: vec![],
+            removed: vec![],
+            invalid: vec![],
+            duplicates: PdfDuplicateReport::default(),
+            excluded: vec![],
+            sorted_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0),
+            manifest: PdfSortManifest::default(),
+            already_sorted: vec![],
+        };
+
+        // receipts the last sort already handled are left alone unless they changed,
+        // a full sort rebuilds the manifest so one that can't be read is started over
+        let mut previous = PdfSortManifest::new_from_dir(out);
+        if previous.is_err() && options.full {
+            println!("the sort manifest in {} could not be read, starting a new one", out);
+            previous = Ok(PdfSortManifest::default());
+        }
+        let previous = previous?;
+        let mut sources: Vec<(PdfLineItem, String, Option<String>)> = vec![];
+        let mut replaceable: Vec<String> = vec![];
+        for item in line_items {
+            let hash = hash_file(&item.path)?;
+            let sidecar_hash = match &item.sidecar {
+                Some(sidecar) => Some(hash_file(&sidecar.path)?),
+                None => None,
+            };
+            let entry = previous.get(&item.trimmed_path);
+            if !options.full && entry.is_some() && entry.unwrap().matches(&hash, &sidecar_hash) {
+                plan.unchanged.push(item.path.clone());
+                continue;
+            }
+            if entry.is_some() && !entry.unwrap().moved {
+                replaceable.extend(entry.unwrap().destinations.iter().cloned());
+            }
+            sources.push((item, hash, sidecar_hash));
+        }
+        let line_items: Vec<PdfLineItem> = sources.iter().map(|(item, _, _)| item.clone()).collect();
+        plan.input_total = line_items.iter().map(|item| item.cost).sum();
+
+        // a rotating remainder carries on from the split receipts earlier sorts shared out
+        let mut turn = previous
+            .entries
+            .iter()
+            .filter(|entry| !entry.allocations.is_empty() && !line_items.iter().any(|item| item.trimmed_path == entry.source))
+            .count();
+
+        // duplicating our split pdfs
+        for item in &line_items {
+            if !item.location.contains("split") {
+                continue;
+            }
+
+            // sharing the cost out to the cent
+            let shares = split_cost(item.cost, &options.split, turn)?;
+            turn += 1;
+            let mut allocations: Vec<PdfSortAllocation> = vec![];
+            for (location, cost) in shares {
+                plan.actions.push(PdfSortPlan::split_action(item, out, options, &location, cost)?);
+                allocations.push(PdfSortAllocation { location: location, cost: cost });
+            }
+            plan.splits.push(PdfSortSplit {
+                source: item.path.clone(),
+                cost: item.cost,
+                allocations: allocations,
+            });
+        }
+
+        // sorting our non-split pdfs
+        for item in &line_items {
+            let location = item.location_name();
+            if location == "split" {
+                continue;
+            }
+            let (destination, convert) = PdfSortPlan::destination(out, options, item)?;
+            plan.actions.push(PdfSortAction {
+                source: item.path.clone(),
+                destination: destination.clone(),
+                location: location,
+                cost: item.cost,
+                split: false,
+                convert: convert,
+                sidecar_source: item.sidecar.as_ref().map(|sidecar| sidecar.path.clone()),
+                sidecar_destination: item.sidecar.as_ref().map(|sidecar| sidecar.path_for(&destination)),
+            });
+        }
+
+        plan.resolve_conflicts(&replaceable)?;
+        plan.update_manifest(dir, previous, &sources);
+
+        // every dir the layout needs, the output root first
+        plan.dirs.push(out.to_owned());
+        let mut dirs: Vec<String> = plan
+            .actions
+            .iter()
+            .filter_map(|action| Path::new(&action.destination).parent().map(|parent| parent.to_string_lossy().to_string()))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        plan.dirs.extend(dirs.into_iter().filter(|dir| dir != out));
+        return Ok(plan);
+    }
+
+    // the manifest as it should read once this plan has been carried out,
+    // sources that were skipped over a conflict stay out so the next run tries again
+    fn update_manifest(&mut self, dir: &str, previous: PdfSortManifest, sources: &[(PdfLineItem, String, Option<String>)]) {
+        let mut entries: Vec<PdfSortManifestEntry> = vec![];
+        for entry in previous.entries {
+            if sources.iter().any(|(item, _, _)| item.trimmed_path == entry.source) {
+                continue;
+            }
+            if !entry.moved && !Path::new(dir).join(&entry.source).is_file() {
+                self.removed.push(entry.source.clone());
+                continue;
+            }
+            entries.push(entry);
+        }
+        for (item, hash, sidecar_hash) in sources {
+            let mut destinations: Vec<String> = self
+                .actions
+                .iter()
+                .filter(|action| action.source == item.path)
+                .map(|action| action.destination.clone())
+                .collect();
+            destinations.extend(
+                self.already_sorted
+                    .iter()
+                    .filter(|(source, _)| *source == item.path)
+                    .map(|(_, destination)| destination.clone()),
+            );
+            if destinations.is_empty() {
+                continue;
+            }
+            let allocations = self
+                .splits
+                .iter()
+                .find(|split| split.source == item.path)
+                .map(|split| split.allocations.clone())
+                .unwrap_or_default();
+            entries.push(PdfSortManifestEntry {
+                source: item.trimmed_path.clone(),
+                hash: hash.clone(),
+                sidecar_hash: sidecar_hash.clone(),
+                destinations: destinations,
+                allocations: allocations,
+                moved: self.mode == PdfSortMode::Move,
+                sorted_at: self.sorted_at,
+            });
+        }
+        entries.sort_by(|a, b| a.source.cmp(&b.source));
+        self.manifest = PdfSortManifest { entries: entries };
+    }
+
+    // finds every taken destination before anything is written, identical
+    // files already in place count as sorted whatever the policy and files an
+    // earlier sort wrote from a receipt that has since changed are replaced.
+    // a receipt's sidecar has to be free too, so it falls under the same policy
+    fn resolve_conflicts(&mut self, replaceable: &[String]) -> Result<(), String> {
+        let mut kept: Vec<PdfSortAction> = vec![];
+        let mut unresolved: Vec<String> = vec![];
+        let actions = std::mem::take(&mut self.actions);
+        for mut action in actions {
+            let on_disk = Path::new(&action.destination).exists();
+            let in_plan = taken_in_plan(&action.destination, &kept);
+            let sidecar_on_disk = action.sidecar_destination.as_ref().is_some_and(|sidecar| Path::new(sidecar).exists());
+            let sidecar_in_plan = action.sidecar_destination.as_ref().is_some_and(|sidecar| taken_in_plan(sidecar, &kept));
+            if !on_disk && !in_plan && !sidecar_on_disk && !sidecar_in_plan {
+                kept.push(action);
+                continue;
+            }
+
+            if on_disk && !in_plan && hash_file(&action.source)? == hash_file(&action.destination)? {
+                self.already_sorted.push((action.source.clone(), action.destination.clone()));
+                self.conflicts.push(PdfSortConflict {
+                    source: action.source.clone(),
+                    destination: action.destination.clone(),
+                    location: action.location.clone(),
+                    cost: action.cost,
+                    reason: "identical file already sorted".to_owned(),
+                    resolution: "skipped".to_owned(),
+                    skipped: true,
+                });
+                continue;
+            }
+
+            // the sidecar an earlier sort wrote next to a changed receipt goes with it
+            if (on_disk || sidecar_on_disk) && !in_plan && !sidecar_in_plan && replaceable.contains(&action.destination) {
+                self.conflicts.push(PdfSortConflict {
+                    source: action.source.clone(),
+                    destination: action.destination.clone(),
+                    location: action.location.clone(),
+                    cost: action.cost,
+                    reason: "receipt changed since the last sort".to_owned(),
+                    resolution: "replaced".to_owned(),
+                    skipped: false,
+                });
+                kept.push(action);
+                continue;
+            }
+
+            // overwrite only replaces files from before this run, a receipt never replaces another one in the same plan
+            let mut policy = self.on_conflict;
+            if (in_plan || sidecar_in_plan) && policy == PdfConflictPolicy::Overwrite {
+                policy = PdfConflictPolicy::Rename;
+            }
+            let receipt_taken = on_disk || in_plan;
+            let reason = match (receipt_taken, in_plan || sidecar_in_plan) {
+                (true, true) => "another receipt sorts to the same name",
+                (true, false) => "a different file already exists",
+                (false, true) => "another receipt's sidecar sorts to the same name",
+                (false, false) => "a different sidecar already exists",
+            };
+            let taken = if receipt_taken { action.destination.clone() } else { action.sidecar_destination.clone().unwrap() };
+            let mut conflict = PdfSortConflict {
+                source: action.source.clone(),
+                destination: taken.clone(),
+                location: action.location.clone(),
+                cost: action.cost,
+                reason: reason.to_owned(),
+                resolution: String::new(),
+                skipped: false,
+            };
+            match policy {
+                PdfConflictPolicy::Fail => {
+                    unresolved.push(format!("{} -> {} ({})", action.source, taken, reason));
+                    conflict.resolution = "failed".to_owned();
+                },
+                PdfConflictPolicy::Skip => {
+                    conflict.resolution = "skipped".to_owned();
+                    conflict.skipped = true;
+                },
+                PdfConflictPolicy::Overwrite => {
+                    conflict.resolution = "overwritten".to_owned();
+                    kept.push(action);
+                },
+                PdfConflictPolicy::Rename => {
+                    let sidecar_ext = action.sidecar_destination.as_ref().and_then(|sidecar| Path::new(sidecar).extension()).map(|ext| ext.to_string_lossy().to_string());
+                    let (destination, identical) = PdfSortPlan::free_destination(&action.source, &action.destination, sidecar_ext.as_deref(), &kept)?;
+                    if identical {
+                        self.already_sorted.push((action.source.clone(), destination.clone()));
+                        conflict.reason = "identical file already sorted under a renamed name".to_owned();
+                        conflict.resolution = format!("skipped, see {}", destination);
+                        conflict.skipped = true;
+                        self.conflicts.push(conflict);
+                        continue;
+                    }
+                    if sidecar_ext.is_some() {
+                        action.sidecar_destination = Some(Path::new(&destination).with_extension(sidecar_ext.unwrap()).to_string_lossy().to_string());
+                    }
+                    conflict.resolution = format!("renamed to {}", destination);
+                    action.destination = destination;
+                    kept.push(action);
+                },
+            }
+            self.conflicts.push(conflict);
+        }
+        self.actions = kept;
+
+        if !unresolved.is_empty() {
+            return Err(format!("SORT CONFLICT: {} destinations are already taken, choose --on-conflict skip, overwrite or rename\n{}", unresolved.len(), unresolved.join("\n")));
+        }
+        return Ok(());
+    }
+
+    /// split sources with a share that will not be in place once the plan is carried out,
+    /// because it was skipped over a conflict. a move leaves these in the inbox
+    pub fn incomplete_splits(&self) -> Vec<String> {
+        return self
+            .splits
+            .iter()
+            .filter(|split| {
+                let written = self.actions.iter().filter(|action| action.split && action.source == split.source).count();
+                let sorted = self.already_sorted.iter().filter(|(source, _)| *source == split.source).count();
+                written + sorted < split.allocations.len()
+            })
+            .map(|split| split.source.clone())
+            .collect();
+    }
+
+    // the first `<description>_<n>` variant of a name that nothing else is using,
+    // along with its sidecar, the suffix goes on the description so the name still
+    // parses. a variant holding the same content as the source is returned flagged as identical
+    fn free_destination(source: &str, destination: &str, sidecar_ext: Option<&str>, taken: &[PdfSortAction]) -> Result<(String, bool), String> {
+        let path = Path::new(destination);
+        let parent = path.parent().map(|parent| parent.to_string_lossy().to_string()).unwrap_or_default();
+        let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let parts: Vec<&str> = file_name.split("-").collect();
+        let mut n = 2;
+        loop {
+            let candidate_name = if parts.len() == 6 {
+                let mut renamed = parts.clone();
+                let description = format!("{}_{}", parts[3], n);
+                renamed[3] = &description;
+                renamed.join("-")
+            } else {
+                format!("{}_{}", file_name, n)
+            };
+            let candidate = format!("{}/{}", parent, candidate_name);
+            let on_disk = Path::new(&candidate).exists();
+            let in_plan = taken_in_plan(&candidate, taken);
+            if on_disk && !in_plan && hash_file(source)? == hash_file(&candidate)? {
+                return Ok((candidate, true));
+            }
+            let sidecar_taken = sidecar_ext.is_some_and(|ext| {
+                let sidecar = Path::new(&candidate).with_extension(ext).to_string_lossy().to_string();
+                Path::new(&sidecar).exists() || taken_in_plan(&sidecar, taken)
+            });
+            if !on_disk && !in_plan && !sidecar_taken {
+                return Ok((candidate, false));
+            }
+            n += 1;
+        }
+    }
+
+    // cloning the line item into one location's share and updating its name
+    fn split_action(item: &PdfLineItem, out: &str, options: &PdfSortOptions, location: &str, cost: Decimal) -> Result<PdfSortAction, String> {
+        let mut share = item.clone();
+        share.set_cost(cost);
+        let err = share.set_location(format!("{}.{}", location, item.extension()));
+        if err.is_some() {
+            return Err(err.unwrap());
+        }
+        let (destination, convert) = PdfSortPlan::destination(out, options, &share)?;
+        let action = PdfSortAction {
+            source: item.path.clone(),
+            destination: destination.clone(),
+            location: location.to_owned(),
+            cost: cost,
+            split: true,
+            convert: convert,
+            sidecar_source: item.sidecar.as_ref().map(|sidecar| sidecar.path.clone()),
+            sidecar_destination: item.sidecar.as_ref().map(|sidecar| sidecar.path_for(&destination)),
+        };
+        return Ok(action);
+    }
+
+    // photographed receipts sort as a pdf of the image when converting
+    fn destination(out: &str, options: &PdfSortOptions, item: &PdfLineItem) -> Result<(String, bool), String> {
+        let destination = layout_destination(out, &options.layout, item)?;
+        if !options.convert_images || !is_image_path(Path::new(&item.path)) {
+            return Ok((destination, false));
+        }
+        return Ok((Path::new(&destination).with_extension("pdf").to_string_lossy().to_string(), true));
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut table = format!("sort plan: {} -> {}/{} ({:?})\n", self.source_dir, self.out_dir, self.layout, self.mode);
+        table += &format!("splits shared with {}\n", self.split);
+        table += &format!("{:<10} {:>10}  {:<6} {} -> {}\n", "LOCATION", "COST", "SPLIT", "SOURCE", "DESTINATION");
+        for action in &self.actions {
+            let split = if action.split { "yes" } else { "no" };
+            table += &format!("{:<10} {:>10}  {:<6} {} -> {}\n", action.location, action.cost, split, action.source, action.destination);
+            if action.sidecar_destination.is_some() {
+                table += &format!("{:<10} {:>10}  {:<6} {} -> {}\n", "", "sidecar", "", action.sidecar_source.as_ref().unwrap(), action.sidecar_destination.as_ref().unwrap());
+            }
+        }
+        for split in &self.splits {
+            let allocations: Vec<String> = split
+                .allocations
+                .iter()
+                .map(|allocation| format!("{} {}", allocation.location, allocation.cost))
+                .collect();
+            table += &format!("split {} ({}) => {}\n", split.source, split.cost, allocations.join(", "));
+        }
+        for conflict in &self.conflicts {
+            table += &format!("conflict {} -> {}: {}, {}\n", conflict.source, conflict.destination, conflict.reason, conflict.resolution);
+        }
+        for removed in &self.removed {
+            table += &format!("removed from source {}\n", removed);
+        }
+        if self.mode == PdfSortMode::Move {
+            for kept in self.incomplete_splits() {
+                table += &format!("kept in source {}, not every share of the split will be written\n", kept);
+            }
+        }
+        for invalid in &self.invalid {
+            table += &format!("skipped invalid {}: {}\n", invalid.path, invalid.reason);
+        }
+        table += &self.duplicates.to_text();
+        for excluded in &self.excluded {
+            table += &format!("excluded duplicate {}\n", excluded);
+        }
+        table += &format!("{} files to write, {} splits, {} conflicts, {} unchanged since the last sort\n", self.actions.len(), self.splits.len(), self.conflicts.len(), self.unchanged.len());
+        return table;
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        let json = serde_json::to_string_pretty(self);
+        if json.is_err() {
+            println!("{}", json.err().unwrap()); // third-party error
+            return Err("JSON FAILURE: failed to serialize the sort plan".to_owned());
+        }
+        return Ok(json.unwrap());
+    }
+
+}
+
+pub const DEFAULT_LAYOUT: &str = "{location}";
+const LAYOUT_FIELDS: [&str; 9] = ["year", "month", "day", "date", "vendor", "cost", "description", "category", "location"];
+
+// a receipt or sidecar the plan already puts at `path`
+fn taken_in_plan(path: &str, taken: &[PdfSortAction]) -> bool {
+    return taken.iter().any(|action| action.destination == path || action.sidecar_destination.as_deref() == Some(path));
+}
+
+/// every `{field}` in the layout must be one a line item can fill in
+pub fn validate_layout(layout: &str) -> Result<(), String> {
+    let field = Regex::new(r"\{([^}]*)\}").unwrap(); // cannot fail
+    for caps in field.captures_iter(layout) {
+        if !LAYOUT_FIELDS.contains(&&caps[1]) {
+            return Err(format!("INVALID LAYOUT: '{{{}}}' is not a layout field, use any of {{{}}}\n{}", &caps[1], LAYOUT_FIELDS.join("}, {"), layout));
+        }
+    }
+    let stripped = field.replace_all(layout, "");
+    if stripped.contains("{") || stripped.contains("}") || layout.starts_with("/") {
+        return Err(format!("INVALID LAYOUT: the layout must be a relative path like '{{year}}/{{month}}/{{location}}'\n{}", layout));
+    }
+    return Ok(());
+}
+
+/// where a line item sorts to, named from its fields so a sidecar override shows in the name,
+/// receipts read from subdirectories land flat in their layout dir
+pub fn layout_destination(out: &str, layout: &str, item: &PdfLineItem) -> Result<String, String> {
+    return Ok(format!("{}/{}", layout_dir(out, layout, item)?, item.file_name()));
+}
+
+/// the dir a line item sorts into, the layout filled in from its fields
+pub fn layout_dir(out: &str, layout: &str, item: &PdfLineItem) -> Result<String, String> {
+    let mut dir = out.trim_end_matches("/").to_owned();
+    for segment in layout.split("/").filter(|segment| !segment.is_empty()) {
+        let segment = segment
+            .replace("{year}", &item.year())
+            .replace("{month}", &item.month())
+            .replace("{day}", &item.day())
+            .replace("{date}", &item.date)
+            .replace("{vendor}", &item.vendor)
+            .replace("{cost}", &item.cost.to_string())
+            .replace("{description}", &item.description)
+            .replace("{category}", &item.category)
+            .replace("{location}", &item.location_name())
+            .replace(['/', '\\'], "_");
+        if segment.trim().is_empty() || segment == "." || segment == ".." {
+            return Err(format!("INVALID LAYOUT: the layout '{}' gives an empty or relative dir for\n{}", layout, item.path));
+        }
+        dir = format!("{}/{}", dir, segment.trim());
+    }
+    return Ok(dir);
+}
@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use genpdf::{elements, style, Alignment, Element};
+use rust_decimal::Decimal;
+
+use crate::pdf_allocate::PdfSplitOptions;
+use crate::pdf_csv::write_csv;
+use crate::pdf_invoice::new_document;
+use crate::pdf_sort_plan::{PdfSortAction, PdfSortConflict, PdfSortPlan, PdfSortSplit};
+use crate::pdf_sorted_dir::PdfSortJournal;
+
+pub const SUMMARY_FILE_STEM: &str = "summary";
+
+/// everything one location received from a sort
+#[derive(Debug, Clone)]
+pub struct PdfSortLocationSummary {
+    pub location: String,
+    pub dir: String,
+    pub count: usize,
+    pub total: Decimal,
+    pub actions: Vec<PdfSortAction>,
+    pub splits: Vec<PdfSortSplit>,
+    pub skipped: Vec<PdfSortConflict>,
+}
+
+impl PdfSortLocationSummary {
+
+    // each run gets its own file so an incremental sort never replaces an earlier summary,
+    // and location folders shared with another location get the location in the file name
+    fn file_stem(&self, shared: bool, sorted_at: u64) -> String {
+        if shared {
+            return format!("{}-{}-{}", SUMMARY_FILE_STEM, self.location, sorted_at);
+        }
+        return format!("{}-{}", SUMMARY_FILE_STEM, sorted_at);
+    }
+
+}
+
+/// what a sort did, checked against the receipts that went in
+#[derive(Debug, Clone)]
+pub struct PdfSortSummary {
+    pub input_total: Decimal,
+    pub locations: Vec<PdfSortLocationSummary>,
+    pub split: PdfSplitOptions,
+    pub splits: Vec<PdfSortSplit>,
+    pub skipped: Vec<PdfSortConflict>,
+    pub unchanged: Vec<String>,
+    pub sorted_at: u64,
+}
+
+impl PdfSortSummary {
+
+    /// built from the plan once its conflicts are resolved, so receipts that were skipped
+    /// or renamed are counted where they really end up
+    pub fn new(plan: &PdfSortPlan) -> Result<PdfSortSummary, String> {
+        let skipped: Vec<PdfSortConflict> = plan.conflicts.iter().filter(|conflict| conflict.skipped).cloned().collect();
+
+        // grouping what was written by location
+        let mut location_names: Vec<String> = plan.actions.iter().map(|action| action.location.clone()).collect();
+        location_names.extend(skipped.iter().map(|conflict| conflict.location.clone()));
+        location_names.sort();
+        location_names.dedup();
+        let mut locations: Vec<PdfSortLocationSummary> = vec![];
+        for location in location_names {
+            let actions: Vec<PdfSortAction> = plan.actions.iter().filter(|action| action.location == location).cloned().collect();
+            let splits: Vec<PdfSortSplit> = plan
+                .splits
+                .iter()
+                .filter(|split| split.allocations.iter().any(|allocation| allocation.location == location))
+                .cloned()
+                .collect();
+            let dir = common_dir(&actions).unwrap_or_else(|| Path::new(&plan.out_dir).join(&location));
+            locations.push(PdfSortLocationSummary {
+                location: location.clone(),
+                dir: dir.to_string_lossy().to_string(),
+                count: actions.len(),
+                total: actions.iter().map(|action| action.cost).sum(),
+                actions: actions,
+                splits: splits,
+                skipped: skipped.iter().filter(|conflict| conflict.location == location).cloned().collect(),
+            });
+        }
+
+        let summary = PdfSortSummary {
+            input_total: plan.input_total,
+            locations: locations,
+            split: plan.split.clone(),
+            splits: plan.splits.clone(),
+            skipped: skipped,
+            unchanged: plan.unchanged.clone(),
+            sorted_at: plan.sorted_at,
+        };
+        let err = summary.verify();
+        if err.is_some() {
+            return Err(err.unwrap());
+        }
+        return Ok(summary);
+    }
+
+    /// every cent that went in is either in a location or was skipped, and every receipt
+    /// counted is one that ends up on disk
+    pub fn verify(&self) -> Option<String> {
+        let mut destinations: Vec<&String> = self.locations.iter().flat_map(|location| location.actions.iter().map(|action| &action.destination)).collect();
+        destinations.sort();
+        for pair in destinations.windows(2) {
+            if pair[0] == pair[1] {
+                return Some(format!("SUMMARY MISMATCH: more than one receipt would be written to the same file\n{}", pair[0]));
+            }
+        }
+        for split in &self.splits {
+            let allocated: Decimal = split.allocations.iter().map(|allocation| allocation.cost).sum();
+            if allocated != split.cost {
+                return Some(format!("SUMMARY MISMATCH: the shares of a split receipt add up to {} instead of {}\n{}", allocated, split.cost, split.source));
+            }
+        }
+        let sorted: Decimal = self.locations.iter().map(|location| location.total).sum();
+        let skipped: Decimal = self.skipped.iter().map(|conflict| conflict.cost).sum();
+        if sorted + skipped != self.input_total {
+            return Some(format!("SUMMARY MISMATCH: {} was sorted and {} skipped but the receipts total {}", sorted, skipped, self.input_total));
+        }
+        return None;
+    }
+
+    /// writes a summary csv and pdf into each location's folder, returning their paths.
+    /// they go through the sort's journal so a failed sort takes them back out too
+    pub fn write(&self, journal: &mut PdfSortJournal) -> Result<Vec<String>, String> {
+        let mut written: Vec<String> = vec![];
+        for location in &self.locations {
+            if location.actions.is_empty() {
+                continue;
+            }
+            let shared = self.locations.iter().any(|other| other.location != location.location && other.dir == location.dir);
+            let stem = Path::new(&location.dir).join(location.file_stem(shared, self.sorted_at));
+            let csv_path = stem.with_extension("csv").to_string_lossy().to_string();
+            let pdf_path = stem.with_extension("pdf").to_string_lossy().to_string();
+            journal.prepare(&csv_path)?;
+            journal.written_files.push(csv_path.clone());
+            PdfSortSummary::write_location_csv(location, &csv_path)?;
+            journal.prepare(&pdf_path)?;
+            journal.written_files.push(pdf_path.clone());
+            PdfSortSummary::write_location_pdf(location, &self.split, &pdf_path)?;
+            written.push(csv_path);
+            written.push(pdf_path);
+        }
+        return Ok(written);
+    }
+
+    fn write_location_csv(location: &PdfSortLocationSummary, path: &str) -> Result<(), String> {
+        let header = ["file", "source", "cost", "split"];
+        let mut rows: Vec<Vec<String>> = location
+            .actions
+            .iter()
+            .map(|action| {
+                vec![
+                    file_name(&action.destination),
+                    action.source.clone(),
+                    action.cost.to_string(),
+                    action.split.to_string(),
+                ]
+            })
+            .collect();
+        rows.push(vec!["total".to_owned(), String::new(), location.total.to_string(), String::new()]);
+        return write_csv(path, &header, &rows);
+    }
+
+    fn write_location_pdf(location: &PdfSortLocationSummary, split: &PdfSplitOptions, path: &str) -> Result<(), String> {
+        let title = format!("{} sort summary", location.location);
+        let mut doc = new_document(&title);
+        let header = elements::Paragraph::new(format!("{}: {}", title, location.total)).aligned(Alignment::Left);
+        doc.push(header.styled(style::Style::new().bold().with_font_size(20)));
+        doc.push(elements::Paragraph::new(format!("{} receipts", location.count)));
+        doc.push(elements::Break::new(1));
+
+        for action in &location.actions {
+            doc.push(elements::Paragraph::new(format!("[{}] {}", action.cost, file_name(&action.destination))));
+        }
+
+        if !location.splits.is_empty() {
+            doc.push(elements::Break::new(1));
+            doc.push(elements::Paragraph::new("split receipts").styled(style::Style::new().bold().with_font_size(16)));
+            doc.push(elements::Paragraph::new(format!("shared with {}", split)));
+            for split in &location.splits {
+                let allocations: Vec<String> = split
+                    .allocations
+                    .iter()
+                    .map(|allocation| format!("{} {}", allocation.location, allocation.cost))
+                    .collect();
+                doc.push(elements::Paragraph::new(format!("[{}] {} => {}", split.cost, file_name(&split.source), allocations.join(", "))));
+            }
+        }
+
+        if !location.skipped.is_empty() {
+            doc.push(elements::Break::new(1));
+            doc.push(elements::Paragraph::new("skipped").styled(style::Style::new().bold().with_font_size(16)));
+            for conflict in &location.skipped {
+                doc.push(elements::Paragraph::new(format!("[{}] {}: {}", conflict.cost, file_name(&conflict.source), conflict.reason)));
+            }
+        }
+
+        let output_file = doc.render_to_file(path);
+        if output_file.is_err() {
+            println!("{:?}", output_file.err().unwrap()); // third-party error
+            return Err(format!("PDF RENDER FAILURE: failed to render output pdf file: {}", path));
+        }
+        return Ok(());
+    }
+
+    /// `summary`, `summary-<run>` or `summary-<location>-<run>`, receipt names have more parts
+    pub fn is_summary_path(path: &Path) -> bool {
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let is_summary_stem = stem == SUMMARY_FILE_STEM || (stem.starts_with(&format!("{}-", SUMMARY_FILE_STEM)) && stem[SUMMARY_FILE_STEM.len() + 1..].split("-").count() <= 2);
+        return is_summary_stem && (ext == "csv" || ext == "pdf");
+    }
+
+}
+
+// the deepest dir holding every file written for a location
+fn common_dir(actions: &[PdfSortAction]) -> Option<PathBuf> {
+    let mut common = Path::new(&actions.first()?.destination).parent()?.to_path_buf();
+    for action in actions {
+        while !Path::new(&action.destination).starts_with(&common) {
+            common = common.parent()?.to_path_buf();
+        }
+    }
+    return Some(common);
+}
+
+fn file_name(path: &str) -> String {
+    return Path::new(path).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+}
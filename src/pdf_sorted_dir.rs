@@ -1,165 +1,296 @@
-
 use std::path::Path;
 use std::fs;
 
-use rust_decimal::Decimal;
-
-use crate::pdf_invoice::PdfLineItem;
+use crate::pdf_duplicates::PdfDuplicateReport;
+use crate::pdf_image::convert_image_to_pdf;
+use crate::pdf_invoice::PdfSkippedFile;
+use crate::pdf_sidecar::PdfSidecar;
+use crate::pdf_sort_manifest::PdfSortManifest;
+use crate::pdf_sort_plan::{PdfSortConflict, PdfSortMode, PdfSortOptions, PdfSortPlan};
+use crate::pdf_sort_summary::PdfSortSummary;
 
+/// the result of carrying out a sort plan
 #[derive(Debug)]
 pub struct PdfSortedDir {
-    dir_root: String,
-    dir_southroads: String,
-    dir_utica: String,
+    pub dir_root: String,
+    pub dirs: Vec<String>,
+    pub files: Vec<String>,
+    pub conflicts: Vec<PdfSortConflict>,
+    pub unchanged: Vec<String>,
+    pub removed: Vec<String>,
+    pub kept: Vec<String>,
+    pub invalid: Vec<PdfSkippedFile>,
+    pub duplicates: PdfDuplicateReport,
+    pub excluded: Vec<String>,
+    pub summary: PdfSortSummary,
+    pub summary_files: Vec<String>,
 }
 
 impl PdfSortedDir {
 
-    pub fn new(dir: &str, out: &str) -> Result<PdfSortedDir, String> {
-
-        // getting the line items
-        let line_items = PdfLineItem::new_from_dir(&dir)?;
+    /// plans and carries out a sort of `dir` into `out`
+    pub fn new(dir: &str, out: &str, options: &PdfSortOptions) -> Result<PdfSortedDir, String> {
+        let plan = PdfSortPlan::new(dir, out, options)?;
+        return PdfSortedDir::new_from_plan(&plan);
+    }
 
-        // generating out paths to create
-        let out_path = Path::new(out);
-        let southroads_out = out_path.join("southroads");
-        let southroads_out = &southroads_out.as_path();
-        let utica_out = out_path.join("utica");
-        let utica_out = &utica_out.as_path();
-        let out_dirs: Vec<&Path> = vec![out_path, southroads_out, utica_out];
+    /// carries out a plan exactly as computed, so a dry run shows what really happens,
+    /// and rolls back everything written so far if any step fails
+    pub fn new_from_plan(plan: &PdfSortPlan) -> Result<PdfSortedDir, String> {
+        // worked out first so a sort that would not add up never touches disk
+        let summary = PdfSortSummary::new(plan)?;
+        let mut journal = PdfSortJournal::default();
+        let written = PdfSortedDir::write_plan(plan, &summary, &mut journal);
+        if written.is_err() {
+            let err = written.err().unwrap();
+            let rollback_errors = journal.rollback();
+            if !rollback_errors.is_empty() {
+                return Err(format!("{}\nROLLBACK FAILURE: the output could not be fully restored\n{}", err, rollback_errors.join("\n")));
+            }
+            return Err(format!("{}\nROLLBACK: every file written by this sort has been removed", err));
+        }
+        journal.commit();
 
-        // creating all the out dirs
-        for inner_dir in out_dirs {
-            let result = fs::create_dir_all(inner_dir);
+        // the output is complete at this point, only the inbox is left to tidy
+        let (pending_removals, summary_files) = written.unwrap();
+        for path in pending_removals {
+            let result = fs::remove_file(&path);
             if result.is_err() {
-                println!("{:?}", result.err().unwrap());
-                return Err(format!("FAILED TO CREATE DIR: the following dir was not created {}", out));
+                println!("{:?}", result.err().unwrap()); // third-party error
+                return Err(format!("FILE REMOVE FAILURE: every share of {} was written but the original could not be removed", path));
             }
         }
 
-        // getting all of the split line items out
-        let mut split_line_items: Vec<PdfLineItem> = vec![];
-        for item in &line_items {
-            if item.location.contains("split") {
-                split_line_items.push(item.clone());
+        // every receipt and sidecar the sort put in place
+        let mut files: Vec<String> = vec![];
+        for action in &plan.actions {
+            files.push(action.destination.clone());
+            if action.sidecar_destination.is_some() {
+                files.push(action.sidecar_destination.clone().unwrap());
             }
         }
+        let sorted_dir = PdfSortedDir {
+            dir_root: plan.out_dir.clone(),
+            dirs: plan.dirs.clone(),
+            files: files,
+            conflicts: plan.conflicts.clone(),
+            unchanged: plan.unchanged.clone(),
+            removed: plan.removed.clone(),
+            kept: if plan.mode == PdfSortMode::Move { plan.incomplete_splits() } else { vec![] },
+            invalid: plan.invalid.clone(),
+            duplicates: plan.duplicates.clone(),
+            excluded: plan.excluded.clone(),
+            summary: summary,
+            summary_files: summary_files,
+        };
+        return Ok(sorted_dir);
+    }
 
-        // duplicating our split pdfs
-        for item in split_line_items {
+    // returns the split and converted sources a move should remove once everything is written,
+    // and the summary files written alongside the receipts
+    fn write_plan(plan: &PdfSortPlan, summary: &PdfSortSummary, journal: &mut PdfSortJournal) -> Result<(Vec<String>, Vec<String>), String> {
 
-            // getting the cost in cents and determining if we are even
-            let cost_in_cents = (item.cost * Decimal::from(100)).round();
-            let item_is_even = cost_in_cents % Decimal::from(2) == Decimal::from(0);
+        // creating all the out dirs
+        for inner_dir in &plan.dirs {
+            journal.create_dir(inner_dir)?;
+        }
 
-            // setting up the cost
-            let mut utica_cost: Decimal;
-            let mut southroads_cost: Decimal;
-            let mut half_cost_in_cents: Decimal;
+        // split sources feed more than one file, so moving them waits until every share is written,
+        // and a split with a share skipped over a conflict stays where it is
+        let mut pending_removals: Vec<String> = vec![];
+        let incomplete = plan.incomplete_splits();
 
-            // manging even / odd costs
-            if item_is_even {
-                half_cost_in_cents = (cost_in_cents) / Decimal::from(2);
-                southroads_cost = half_cost_in_cents / Decimal::from(100);
-                utica_cost  = half_cost_in_cents / Decimal::from(100);
+        for action in &plan.actions {
+            let mut mode = plan.mode;
+            let removable = plan.mode == PdfSortMode::Move && !incomplete.contains(&action.source);
+            if action.split && mode == PdfSortMode::Move {
+                mode = PdfSortMode::Copy;
+                if removable && !pending_removals.contains(&action.source) {
+                    pending_removals.push(action.source.clone());
+                }
+            }
+            if action.convert {
+                // the pdf is a new file, so a move removes the image once the sort is done
+                journal.prepare(&action.destination)?;
+                journal.written_files.push(action.destination.clone());
+                convert_image_to_pdf(&action.source, &action.destination)?;
+                if removable && !pending_removals.contains(&action.source) {
+                    pending_removals.push(action.source.clone());
+                }
             } else {
-                half_cost_in_cents = (cost_in_cents - Decimal::from(1)) / Decimal::from(2);
-                southroads_cost = half_cost_in_cents / Decimal::from(100);
-                utica_cost  = (half_cost_in_cents + Decimal::from(1)) / Decimal::from(100);
+                journal.transfer_file(mode, &action.source, &action.destination)?;
             }
 
-            // sanity check
-            if (utica_cost + southroads_cost) != item.cost {
-                return Err(format!("PDF SPLIT ERROR: when splitting a PdfLineItem, the cost of the two pdfs does not equal the total cost of the original\n{:?}", item));
+            // carrying the sidecar along, split shares get their own cost and location
+            if action.sidecar_source.is_some() {
+                let sidecar_source = action.sidecar_source.as_ref().unwrap();
+                let sidecar_destination = action.sidecar_destination.as_ref().unwrap();
+                if action.split {
+                    let sidecar = PdfSidecar::new(sidecar_source)?;
+                    journal.prepare(sidecar_destination)?;
+                    journal.written_files.push(sidecar_destination.clone());
+                    sidecar.write_for_receipt(&action.destination, &[("cost", action.cost.to_string()), ("location", action.location.clone())])?;
+                    if removable && !pending_removals.contains(sidecar_source) {
+                        pending_removals.push(sidecar_source.clone());
+                    }
+                } else {
+                    journal.transfer_file(mode, sidecar_source, sidecar_destination)?;
+                }
             }
+        }
 
-            // cloning line item into two and updating costs
-            let mut utica_line_item = item.clone();
-            utica_line_item.set_cost(utica_cost);
-            let err = utica_line_item.set_location("utica.pdf".to_owned());
-            if err.is_some() {
-                return Err(err.unwrap());
-            }
-            let mut southroads_line_item = item.clone().to_owned();
-            southroads_line_item.set_cost(southroads_cost);
-            let err = southroads_line_item.set_location("southroads.pdf".to_owned());
-            if err.is_some() {
-                return Err(err.unwrap())
-            }
+        let summary_files = summary.write(journal)?;
 
-            // setting up new paths for output files
-            utica_line_item.set_source_dir(&(out.to_owned() + "/utica"));
-            southroads_line_item.set_source_dir(&(out.to_owned() + "/southroads"));
-
-            // getting the details for copying/pasting
-            let source_dest = item.path;
-            let utica_out = utica_line_item.path;
-            let southroads_out = southroads_line_item.path;
-
-            // copying the files
-            let utica_file = fs::copy(source_dest.clone(), utica_out.clone());
-            if utica_file.is_err() {
-                let err = utica_file.err().unwrap();
-                println!("{}", err);
-                return Err(format!("FILE COPY FAILURE: failed to copy {} to {}", source_dest, utica_out))
-            }
-            let southroads_file = fs::copy(source_dest.clone(), southroads_out.clone());
-            if southroads_file.is_err() {
-                let err = southroads_file.err().unwrap();
-                println!("{}", err);
-                return Err(format!("FILE COPY FAILURE: failed to copy {} to {}", source_dest, southroads_out))
-            }
+        // the manifest goes last so it only ever describes a finished sort
+        let manifest_path = PdfSortManifest::path_for(&plan.out_dir);
+        journal.prepare(&manifest_path)?;
+        journal.written_files.push(manifest_path.clone());
+        plan.manifest.save(&manifest_path)?;
 
+        return Ok((pending_removals, summary_files));
+    }
 
+}
 
+/// everything a sort has changed on disk, so a failed run can be undone
+#[derive(Debug, Default)]
+pub struct PdfSortJournal {
+    pub created_dirs: Vec<String>,
+    pub written_files: Vec<String>,
+    pub moved_files: Vec<(String, String)>,
+    pub backups: Vec<(String, String)>,
+}
 
-        };
+impl PdfSortJournal {
 
-        // sorting our non-split pdfs
-        for mut item in line_items {
-            
-            // skipping split locatons
-            let location = item.location.clone();
-            if location.contains("split") {
-                continue;
+    /// records each dir that does not exist yet, outermost first
+    pub fn create_dir(&mut self, dir: &str) -> Result<(), String> {
+        let mut missing: Vec<String> = vec![];
+        let mut current = Some(Path::new(dir));
+        while let Some(path) = current {
+            if path.as_os_str().is_empty() || path.exists() {
+                break;
             }
-            
-            // getting copy/paste destinations
-            let source_dest = item.path.clone();
-            
-            // copy/pasting for southroads
-            if location.contains("southroads") {
-                item.set_source_dir(&(out.to_owned() + "/southroads"));
-                let file = fs::copy(source_dest.clone(), item.path.clone());
-                if file.is_err() {
-                    let err = file.err().unwrap();
-                    println!("{}", err);
-                    return Err(format!("FILE COPY FAILURE: failed to copy {} to {}", source_dest, item.path))
-                }
-                continue;
+            missing.push(path.to_string_lossy().to_string());
+            current = path.parent();
+        }
+        let result = fs::create_dir_all(dir);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap());
+            return Err(format!("FAILED TO CREATE DIR: the following dir was not created {}", dir));
+        }
+        for path in missing.into_iter().rev() {
+            if !self.created_dirs.contains(&path) {
+                self.created_dirs.push(path);
             }
+        }
+        return Ok(());
+    }
 
-            // copy/pasting for utica
-            if location.contains("utica") {
-                item.set_source_dir(&(out.to_owned() + "/utica"));
-                let file = fs::copy(source_dest.clone(), item.path.clone());
-                if file.is_err() {
-                    let err = file.err().unwrap();
-                    println!("{}", err);
-                    return Err(format!("FILE COPY FAILURE: failed to copy {} to {}", source_dest, item.path))
-                }
+    /// moves an existing destination aside so a rollback can put it back
+    pub fn prepare(&mut self, destination: &str) -> Result<(), String> {
+        // only files from before this run are set aside, never one it wrote itself
+        let written = self.written_files.iter().any(|path| path == destination);
+        if written || self.moved_files.iter().any(|(_, path)| path == destination) {
+            return Err(format!("SORT CONFLICT: this sort already wrote {} and will not replace it", destination));
+        }
+        let path = Path::new(destination);
+        if !path.exists() && !path.is_symlink() {
+            return Ok(());
+        }
+        let backup = format!("{}.finli-backup", destination);
+        let result = fs::rename(destination, &backup);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("BACKUP FAILURE: failed to set aside the existing file {}", destination));
+        }
+        self.backups.push((destination.to_owned(), backup));
+        return Ok(());
+    }
+
+    pub fn transfer_file(&mut self, mode: PdfSortMode, source: &str, destination: &str) -> Result<(), String> {
+        self.prepare(destination)?;
+        if mode == PdfSortMode::Move {
+            transfer_file(mode, source, destination)?;
+            self.moved_files.push((source.to_owned(), destination.to_owned()));
+            return Ok(());
+        }
+        // recorded up front so a partly written file is cleaned up too
+        self.written_files.push(destination.to_owned());
+        return transfer_file(mode, source, destination);
+    }
+
+    /// undoes every change in reverse, returning anything that could not be undone
+    pub fn rollback(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        for path in self.written_files.iter().rev() {
+            let exists = Path::new(path).exists() || Path::new(path).is_symlink();
+            if exists && fs::remove_file(path).is_err() {
+                errors.push(format!("could not remove {}", path));
+            }
+        }
+        for (source, destination) in self.moved_files.iter().rev() {
+            if move_file(destination, source).is_err() {
+                errors.push(format!("could not move {} back to {}", destination, source));
+            }
+        }
+        for (original, backup) in self.backups.iter().rev() {
+            if fs::rename(backup, original).is_err() {
+                errors.push(format!("could not restore {} from {}", original, backup));
+            }
+        }
+        for dir in self.created_dirs.iter().rev() {
+            if fs::remove_dir(dir).is_err() {
+                errors.push(format!("could not remove dir {}", dir));
             }
+        }
+        return errors;
+    }
 
+    /// the run succeeded, so the files that were set aside are no longer needed
+    pub fn commit(&self) {
+        for (_, backup) in &self.backups {
+            let _ = fs::remove_file(backup);
         }
+    }
 
+}
 
+/// puts `source` at `destination` using the given mode
+pub fn transfer_file(mode: PdfSortMode, source: &str, destination: &str) -> Result<(), String> {
+    let result = match mode {
+        PdfSortMode::Copy => fs::copy(source, destination).map(|_| ()),
+        PdfSortMode::Move => move_file(source, destination),
+        PdfSortMode::Hardlink => fs::hard_link(source, destination),
+        PdfSortMode::Reflink => reflink_copy::reflink(source, destination),
+        PdfSortMode::Symlink => symlink_file(source, destination),
+    };
+    if result.is_err() {
+        let err = result.err().unwrap();
+        println!("{}", err);
+        if mode == PdfSortMode::Reflink {
+            return Err(format!("FILE REFLINK FAILURE: failed to reflink {} to {}, the filesystem may not support reflinks so try --mode copy", source, destination));
+        }
+        let mode = format!("{:?}", mode).to_uppercase();
+        return Err(format!("FILE {} FAILURE: failed to {} {} to {}", mode, mode.to_lowercase(), source, destination));
+    }
+    return Ok(());
+}
 
-        let sorted_dir = PdfSortedDir {
-            dir_root: out_path.to_str().unwrap().to_owned(), // cannot fail
-            dir_southroads: southroads_out.to_str().unwrap().to_owned(), // cannot fail
-            dir_utica: utica_out.to_str().unwrap().to_owned(), // cannot fail
-        };
-        return Ok(sorted_dir);
+// renaming only works within a filesystem, otherwise copy then remove
+fn move_file(source: &str, destination: &str) -> std::io::Result<()> {
+    if fs::rename(source, destination).is_ok() {
+        return Ok(());
     }
+    fs::copy(source, destination)?;
+    return fs::remove_file(source);
+}
 
+// links point at the absolute source so they resolve from inside the output dir
+fn symlink_file(source: &str, destination: &str) -> std::io::Result<()> {
+    let source = fs::canonicalize(source)?;
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(source, destination);
+    #[cfg(windows)]
+    return std::os::windows::fs::symlink_file(source, destination);
 }
@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::pdf_add::PdfKnownValues;
+use crate::pdf_config::PdfConfig;
+use crate::pdf_rename::{normalize_cost, normalize_date};
+use crate::pdf_text::{find_amounts, PdfText};
+
+/// fields proposed for a receipt by reading its text layer
+#[derive(Debug, Clone)]
+pub struct PdfSuggestion {
+    pub path: String,
+    pub has_text: bool,
+    pub date: Option<String>,
+    pub vendor: Option<String>,
+    pub total: Option<Decimal>,
+    pub file_cost: Option<Decimal>,
+    pub error: Option<String>,
+}
+
+impl PdfSuggestion {
+
+    pub fn new(path: &str, config: &PdfConfig, known_vendors: &[String]) -> Result<PdfSuggestion, String> {
+        let text = PdfText::new(path)?;
+        let mut suggestion = PdfSuggestion {
+            path: path.to_owned(),
+            has_text: text.has_text(),
+            date: None,
+            vendor: None,
+            total: None,
+            file_cost: file_cost(path),
+            error: None,
+        };
+        if !suggestion.has_text {
+            return Ok(suggestion);
+        }
+
+        // vendor specific rules from the config win over the generic heuristics
+        for rule in &config.suggest {
+            let pattern = compile_rule(&rule.pattern, &rule.vendor)?;
+            if !pattern.is_match(&text.text) {
+                continue;
+            }
+            suggestion.vendor = Some(rule.vendor.to_lowercase());
+            if let Some(total) = &rule.total {
+                let total = compile_rule(total, &rule.vendor)?;
+                suggestion.total = total
+                    .captures(&text.text)
+                    .and_then(|caps| caps.get(1))
+                    .and_then(|value| find_amounts(value.as_str()).first().copied());
+            }
+            if let Some(date) = &rule.date {
+                let date = compile_rule(date, &rule.vendor)?;
+                suggestion.date = date
+                    .captures(&text.text)
+                    .and_then(|caps| caps.get(1))
+                    .and_then(|value| normalize_date(&value.as_str().replace("-", "/")));
+            }
+            break;
+        }
+
+        if suggestion.vendor.is_none() {
+            suggestion.vendor = guess_vendor(&text, config, known_vendors);
+        }
+        if suggestion.date.is_none() {
+            suggestion.date = text.dates().first().cloned();
+        }
+        if suggestion.total.is_none() {
+            suggestion.total = guess_total(&text);
+        }
+        return Ok(suggestion);
+    }
+
+    pub fn new_from_dir(dir: &str, config: &PdfConfig) -> Result<Vec<PdfSuggestion>, String> {
+        let dir_path = Path::new(dir);
+        if !dir_path.is_dir() {
+            return Err(format!("MISSING DIR: this dir does not exist: {:?}", dir_path));
+        }
+        let entries = fs::read_dir(dir_path);
+        if entries.is_err() {
+            println!("{:?}", entries.err().unwrap()); // third-party error
+            return Err(format!("READ DIR FAILURE: failed to read the contents of {}", dir));
+        }
+        let mut paths: Vec<String> = vec![];
+        for entry in entries.unwrap().flatten() {
+            let path = entry.path();
+            let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            if path.is_file() && ext.to_lowercase() == "pdf" {
+                paths.push(format!("{}/{}", dir, entry.file_name().to_string_lossy()));
+            }
+        }
+        paths.sort();
+
+        let known = PdfKnownValues::new_from_dir(dir);
+        // one unreadable receipt should not hide the suggestions for the rest
+        let mut suggestions: Vec<PdfSuggestion> = vec![];
+        for path in paths {
+            let suggestion = PdfSuggestion::new(&path, config, &known.vendors);
+            if suggestion.is_ok() {
+                suggestions.push(suggestion.unwrap());
+                continue;
+            }
+            let err = suggestion.err().unwrap();
+            if err.starts_with("INVALID SUGGEST RULE") {
+                return Err(err);
+            }
+            suggestions.push(PdfSuggestion {
+                path: path.clone(),
+                has_text: false,
+                date: None,
+                vendor: None,
+                total: None,
+                file_cost: file_cost(&path),
+                error: Some(err),
+            });
+        }
+        return Ok(suggestions);
+    }
+
+    /// the file name claims one amount but the document shows another
+    pub fn amount_mismatch(&self) -> bool {
+        if self.file_cost.is_none() || self.total.is_none() {
+            return false;
+        }
+        return self.file_cost.unwrap() != self.total.unwrap();
+    }
+
+}
+
+fn compile_rule(pattern: &str, vendor: &str) -> Result<Regex, String> {
+    let regex = Regex::new(pattern);
+    if regex.is_err() {
+        println!("{}", regex.err().unwrap()); // third-party error
+        return Err(format!("INVALID SUGGEST RULE: the pattern for vendor '{}' is not a valid regex\n{}", vendor, pattern));
+    }
+    return Ok(regex.unwrap());
+}
+
+// the cost part of a `[DATE]-[VENDOR]-[COST]-...` name, if there is one
+fn file_cost(path: &str) -> Option<Decimal> {
+    let stem = Path::new(path).file_stem()?.to_str()?;
+    let parts: Vec<&str> = stem.split("-").collect();
+    if parts.len() != 6 {
+        return None;
+    }
+    return Decimal::from_str(&normalize_cost(parts[2])?).ok();
+}
+
+// a known vendor named in the text, otherwise the first word of the first line
+fn guess_vendor(text: &PdfText, config: &PdfConfig, known_vendors: &[String]) -> Option<String> {
+    let lowered = text.text.to_lowercase();
+    let mut candidates: Vec<String> = known_vendors.to_vec();
+    for (alias, vendor) in &config.aliases.vendor {
+        candidates.push(alias.to_lowercase());
+        candidates.push(vendor.to_lowercase());
+    }
+    for candidate in candidates {
+        if !candidate.is_empty() && lowered.contains(&candidate) {
+            return Some(config.aliases.vendor(&candidate));
+        }
+    }
+    let first_line = text.lines().first()?.to_lowercase();
+    let word: String = first_line
+        .split_whitespace()
+        .next()?
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    if word.is_empty() {
+        return None;
+    }
+    return Some(config.aliases.vendor(&word));
+}
+
+// the largest amount on a line mentioning a total (but not a subtotal),
+// falling back to the largest amount in the document
+fn guess_total(text: &PdfText) -> Option<Decimal> {
+    let mut totals: Vec<Decimal> = vec![];
+    for line in text.lines() {
+        let line = line.to_lowercase();
+        if !line.contains("total") || line.contains("subtotal") || line.contains("sub total") {
+            continue;
+        }
+        totals.extend(find_amounts(&line));
+    }
+    if totals.is_empty() {
+        totals = text.amounts();
+    }
+    return totals.into_iter().max();
+}
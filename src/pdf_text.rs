@@ -0,0 +1,103 @@
+use std::panic;
+use std::path::Path;
+use std::str::FromStr;
+
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::pdf_image::is_image_path;
+use crate::pdf_rename::normalize_date;
+
+/// the text layer of a receipt, empty for scans without one
+#[derive(Debug, Clone)]
+pub struct PdfText {
+    pub text: String,
+}
+
+impl PdfText {
+
+    pub fn new(path: &str) -> Result<PdfText, String> {
+        // photographed receipts have no text layer to read
+        if is_image_path(Path::new(path)) {
+            return Ok(PdfText { text: String::new() });
+        }
+
+        // the extractor can panic on malformed documents, treat that as a failure
+        let owned_path = path.to_owned();
+        let text = panic::catch_unwind(move || pdf_extract::extract_text(owned_path));
+        if text.is_err() {
+            return Err(format!("TEXT EXTRACTION FAILURE: the pdf text layer could not be read\n{}", path));
+        }
+        let text = text.unwrap();
+        if text.is_err() {
+            println!("{:?}", text.err().unwrap()); // third-party error
+            return Err(format!("TEXT EXTRACTION FAILURE: the pdf text layer could not be read\n{}", path));
+        }
+        let pdf_text = PdfText {
+            text: text.unwrap(),
+        };
+        return Ok(pdf_text);
+    }
+
+    pub fn has_text(&self) -> bool {
+        return !self.text.trim().is_empty();
+    }
+
+    pub fn lines(&self) -> Vec<&str> {
+        return self.text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).collect();
+    }
+
+    /// every amount that looks like money, e.g. '$1,024.50' or '10.95'
+    pub fn amounts(&self) -> Vec<Decimal> {
+        return find_amounts(&self.text);
+    }
+
+    /// every date in the text, normalized to MMDDYY
+    pub fn dates(&self) -> Vec<String> {
+        let mut dates: Vec<String> = vec![];
+        let numeric = Regex::new(r"\b(\d{1,2})[/.-](\d{1,2})[/.-](\d{2,4})\b").unwrap(); // cannot fail
+        let iso = Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap(); // cannot fail
+        let named = Regex::new(r"(?i)\b(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+(\d{1,2}),?\s+(\d{4})\b").unwrap(); // cannot fail
+        for caps in iso.captures_iter(&self.text) {
+            if let Some(date) = normalize_date(&format!("{}/{}/{}", &caps[1], &caps[2], &caps[3])) {
+                dates.push(date);
+            }
+        }
+        for caps in numeric.captures_iter(&self.text) {
+            if let Some(date) = normalize_date(&format!("{}/{}/{}", &caps[1], &caps[2], &caps[3])) {
+                dates.push(date);
+            }
+        }
+        let months = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+        for caps in named.captures_iter(&self.text) {
+            let month = months.iter().position(|month| *month == caps[1].to_lowercase()).unwrap_or(0) + 1;
+            if let Some(date) = normalize_date(&format!("{}/{}/{}", month, &caps[2], &caps[3])) {
+                dates.push(date);
+            }
+        }
+        return dates;
+    }
+
+    /// allows for '$', thousands separators and a missing trailing zero
+    pub fn contains_amount(&self, amount: Decimal) -> bool {
+        return self.amounts().contains(&amount);
+    }
+
+    /// allows for any of the date formats understood by `dates`
+    pub fn contains_date(&self, date: &str) -> bool {
+        return self.dates().iter().any(|found| found == date);
+    }
+
+}
+
+pub fn find_amounts(text: &str) -> Vec<Decimal> {
+    let money = Regex::new(r"\$?\s?(\d{1,3}(?:,\d{3})+|\d+)\.(\d{1,2})\b").unwrap(); // cannot fail
+    let mut amounts: Vec<Decimal> = vec![];
+    for caps in money.captures_iter(text) {
+        let amount = format!("{}.{}", caps[1].replace(",", ""), &caps[2]);
+        if let Ok(amount) = Decimal::from_str(&amount) {
+            amounts.push(amount);
+        }
+    }
+    return amounts;
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::Path;
+
+use lopdf::{Document, Error};
+use serde::Deserialize;
+
+use crate::pdf_image::is_image_path;
+
+// the pdf spec lets the header sit anywhere in the first 1024 bytes
+const HEADER_WINDOW: usize = 1024;
+
+/// the `[verify]` section of the config
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PdfVerifyOptions {
+    #[serde(default)]
+    pub allow_encrypted: bool,
+}
+
+/// what opening a receipt found, password protected files allowed through have no page count
+#[derive(Debug, Clone)]
+pub struct PdfVerification {
+    pub path: String,
+    pub pages: Option<usize>,
+    pub encrypted: bool,
+}
+
+impl PdfVerification {
+
+    /// makes sure a receipt is a file that will actually open, not just one with the right extension
+    pub fn new(path: &str, options: &PdfVerifyOptions) -> Result<PdfVerification, String> {
+        let bytes = fs::read(path);
+        if bytes.is_err() {
+            println!("{:?}", bytes.err().unwrap()); // third-party error
+            return Err(format!("FILE READ FAILURE: failed to read the receipt\n{}", path));
+        }
+        let bytes = bytes.unwrap();
+        if bytes.is_empty() {
+            return Err(format!("EMPTY FILE: the receipt is zero bytes\n{}", path));
+        }
+
+        if is_image_path(Path::new(path)) {
+            let format = image::guess_format(&bytes);
+            if format.is_err() || image::load_from_memory_with_format(&bytes, format.unwrap()).is_err() {
+                return Err(format!("CORRUPT IMAGE: the receipt image could not be decoded\n{}", path));
+            }
+            return Ok(PdfVerification { path: path.to_owned(), pages: Some(1), encrypted: false });
+        }
+
+        let window = &bytes[..bytes.len().min(HEADER_WINDOW)];
+        if !window.windows(5).any(|start| start == b"%PDF-") {
+            return Err(format!("INVALID PDF HEADER: the file does not start like a pdf, it may be another kind of file renamed to .pdf\n{}", path));
+        }
+
+        let document = Document::load_mem(&bytes);
+        if document.is_err() {
+            let err = document.err().unwrap();
+            let password_protected = matches!(err, Error::Decryption(_) | Error::InvalidPassword);
+            if !password_protected {
+                println!("{}", err); // third-party error
+                return Err(format!("CORRUPT PDF: the document structure could not be read\n{}", path));
+            }
+            if !options.allow_encrypted {
+                return Err(format!("ENCRYPTED PDF: the receipt is password protected, set allow_encrypted under [verify] in the config to accept it\n{}", path));
+            }
+            return Ok(PdfVerification { path: path.to_owned(), pages: None, encrypted: true });
+        }
+        let document = document.unwrap();
+
+        // files with an empty user password are decrypted on load, anything still encrypted needs a password
+        let password_protected = document.is_encrypted();
+        let encrypted = password_protected || document.was_encrypted();
+        if encrypted && !options.allow_encrypted {
+            let kind = if password_protected { "password protected" } else { "encrypted" };
+            return Err(format!("ENCRYPTED PDF: the receipt is {}, set allow_encrypted under [verify] in the config to accept it\n{}", kind, path));
+        }
+        if password_protected {
+            return Ok(PdfVerification { path: path.to_owned(), pages: None, encrypted: true });
+        }
+        let pages = document.get_pages().len();
+        if pages == 0 {
+            return Err(format!("EMPTY PDF: the document has no pages\n{}", path));
+        }
+        return Ok(PdfVerification { path: path.to_owned(), pages: Some(pages), encrypted: encrypted });
+    }
+
+}
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use notify::{RecursiveMode, Watcher};
+
+use crate::pdf_ignore::PdfIgnore;
+use crate::pdf_invoice::{is_receipt_path, receipt_for_sidecar, PdfLineItem};
+use crate::pdf_sidecar::{PdfSidecar, PdfSidecarFormat};
+use crate::pdf_sort_plan::{PdfSortMode, PdfSortOptions, PdfSortPlan};
+use crate::pdf_sorted_dir::{transfer_file, PdfSortedDir};
+use crate::pdf_verify::{PdfVerification, PdfVerifyOptions};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// a receipt that is still being written, or has not been quiet for long enough
+#[derive(Debug, Clone)]
+struct PdfPendingFile {
+    size: u64,
+    changed_at: Instant,
+}
+
+/// sorts receipts into the output as they land in the inbox
+#[derive(Debug)]
+pub struct PdfWatcher {
+    pub inbox: String,
+    pub out: String,
+    pub options: PdfSortOptions,
+    pub verify: PdfVerifyOptions,
+    pub settle: Duration,
+    inbox_path: PathBuf,
+    ignore: PdfIgnore,
+    pending: BTreeMap<String, PdfPendingFile>,
+}
+
+impl PdfWatcher {
+
+    pub fn new(inbox: &str, out: &str, options: PdfSortOptions, verify: PdfVerifyOptions, settle: Duration) -> Result<PdfWatcher, String> {
+        let inbox_path = Path::new(inbox);
+        if !inbox_path.is_dir() {
+            return Err(format!("MISSING DIR: this dir does not exist: {:?}", inbox_path));
+        }
+        let inbox_path = inbox_path.canonicalize();
+        if inbox_path.is_err() {
+            println!("{:?}", inbox_path.err().unwrap()); // third-party error
+            return Err(format!("WATCH FAILURE: failed to resolve the inbox {}", inbox));
+        }
+        let watcher = PdfWatcher {
+            inbox: inbox.trim_end_matches("/").to_owned(),
+            out: out.to_owned(),
+            options: options,
+            verify: verify,
+            settle: settle,
+            inbox_path: inbox_path.unwrap(),
+            ignore: PdfIgnore::new_from_dir(inbox)?,
+            pending: BTreeMap::new(),
+        };
+        return Ok(watcher);
+    }
+
+    /// runs until the watch itself breaks, a bad receipt only ever rejects that receipt
+    pub fn run(&mut self) -> Result<(), String> {
+        let (sender, receiver) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender);
+        if watcher.is_err() {
+            println!("{:?}", watcher.err().unwrap()); // third-party error
+            return Err("WATCH FAILURE: failed to start watching the filesystem".to_owned());
+        }
+        let mut watcher = watcher.unwrap();
+        let result = watcher.watch(&self.inbox_path, RecursiveMode::NonRecursive);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("WATCH FAILURE: failed to watch {}", self.inbox));
+        }
+
+        // receipts that were already waiting when the watch started
+        let entries = fs::read_dir(&self.inbox_path);
+        if entries.is_err() {
+            println!("{:?}", entries.err().unwrap()); // third-party error
+            return Err(format!("READ DIR FAILURE: failed to read the contents of {}", self.inbox));
+        }
+        for entry in entries.unwrap().flatten() {
+            self.track(&entry.path());
+        }
+
+        println!("watching {} and sorting into {}", self.inbox, self.out);
+        loop {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(Ok(event)) => {
+                    for path in &event.paths {
+                        self.track(path);
+                    }
+                },
+                Ok(Err(err)) => println!("{:?}", err), // third-party error
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!("WATCH FAILURE: stopped receiving filesystem events for {}", self.inbox));
+                },
+            }
+            for path in self.settled() {
+                self.handle(&path);
+            }
+        }
+    }
+
+    // notes activity on a receipt in the inbox, a changed sidecar counts for its receipt
+    fn track(&mut self, path: &Path) {
+        let parent = path.parent().and_then(|parent| parent.canonicalize().ok());
+        if parent.as_ref() != Some(&self.inbox_path) {
+            return;
+        }
+        let mut path = path.to_path_buf();
+        if PdfSidecar::is_sidecar_path(&path) {
+            let receipt = receipt_for_sidecar(&path);
+            if receipt.is_none() {
+                return;
+            }
+            path = receipt.unwrap();
+        }
+        if !is_receipt_path(&path) || !path.is_file() {
+            return;
+        }
+        let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        if self.ignore.is_ignored(Path::new(&file_name), false) {
+            return;
+        }
+        let size = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+        self.pending.insert(format!("{}/{}", self.inbox, file_name), PdfPendingFile {
+            size: size,
+            changed_at: Instant::now(),
+        });
+    }
+
+    // receipts whose size has held still for the settle time, so the scanner is done writing
+    fn settled(&mut self) -> Vec<String> {
+        let mut settled: Vec<String> = vec![];
+        let mut gone: Vec<String> = vec![];
+        for (path, pending) in self.pending.iter_mut() {
+            let metadata = fs::metadata(path);
+            if metadata.is_err() {
+                gone.push(path.clone());
+                continue;
+            }
+            let size = metadata.unwrap().len();
+            if size != pending.size || size == 0 {
+                pending.size = size;
+                pending.changed_at = Instant::now();
+                continue;
+            }
+            if pending.changed_at.elapsed() >= self.settle {
+                settled.push(path.clone());
+            }
+        }
+        for path in gone.iter().chain(settled.iter()) {
+            self.pending.remove(path);
+        }
+        return settled;
+    }
+
+    fn handle(&self, path: &str) {
+        let sorted = self.sort_file(path);
+        if sorted.is_ok() {
+            let sorted = sorted.unwrap();
+            if sorted.unchanged.is_empty() {
+                println!("sorted {}", path);
+            }
+            for conflict in &sorted.conflicts {
+                println!("conflict {} -> {}: {}, {}", conflict.source, conflict.destination, conflict.reason, conflict.resolution);
+            }
+            for kept in &sorted.kept {
+                println!("kept {}, not every share of the split was written", kept);
+            }
+            return;
+        }
+        let err = sorted.err().unwrap();
+        let rejected = self.reject(path, &err);
+        if rejected.is_err() {
+            println!("{}\n{}", err, rejected.err().unwrap());
+            return;
+        }
+        println!("rejected {} -> {}\n{}", path, rejected.unwrap(), err);
+    }
+
+    fn sort_file(&self, path: &str) -> Result<PdfSortedDir, String> {
+        let line_item = PdfLineItem::new_with_sidecar(&self.inbox, path)?;
+        PdfVerification::new(path, &self.verify)?;
+        let plan = PdfSortPlan::new_from_line_items(&self.inbox, &self.out, vec![line_item], &self.options)?;
+        return PdfSortedDir::new_from_plan(&plan);
+    }
+
+    // moves a receipt that could not be sorted into `<out>/rejected` next to a note saying why
+    fn reject(&self, path: &str, err: &str) -> Result<String, String> {
+        let rejected_dir = Path::new(&self.out).join("rejected");
+        let result = fs::create_dir_all(&rejected_dir);
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("FAILED TO CREATE DIR: the following dir was not created {:?}", rejected_dir));
+        }
+        let file_name = Path::new(path).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let destination = rejected_dir.join(&file_name).to_string_lossy().to_string();
+        transfer_file(PdfSortMode::Move, path, &destination)?;
+        for format in [PdfSidecarFormat::Toml, PdfSidecarFormat::Json] {
+            let sidecar_path = PdfSidecar::path_for_receipt(path, &format);
+            if Path::new(&sidecar_path).is_file() {
+                let sidecar_name = Path::new(&sidecar_path).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                transfer_file(PdfSortMode::Move, &sidecar_path, &rejected_dir.join(sidecar_name).to_string_lossy())?;
+            }
+        }
+        let error_path = rejected_dir.join(format!("{}.error.txt", file_name));
+        let result = fs::write(&error_path, format!("{}\n", err));
+        if result.is_err() {
+            println!("{:?}", result.err().unwrap()); // third-party error
+            return Err(format!("FILE WRITE FAILURE: failed to write {:?}", error_path));
+        }
+        return Ok(destination);
+    }
+
+}
@@ -0,0 +1,179 @@
+use finli::pdf_allocate::{allocate, allocate_with, remainder_priority, split_cost, PdfRemainderPolicy, PdfRounding, PdfSplitOptions};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+fn weights() -> impl Strategy<Value = Vec<Decimal>> {
+    return prop::collection::vec(0u32..10_000, 1..8)
+        .prop_filter("at least one weight above zero", |weights| weights.iter().any(|weight| *weight > 0))
+        .prop_map(|weights| weights.into_iter().map(Decimal::from).collect());
+}
+
+fn rounding() -> impl Strategy<Value = PdfRounding> {
+    return prop_oneof![Just(PdfRounding::HalfUp), Just(PdfRounding::Bankers)];
+}
+
+fn policy() -> impl Strategy<Value = PdfRemainderPolicy> {
+    return prop_oneof![
+        Just(PdfRemainderPolicy::First),
+        Just(PdfRemainderPolicy::Rotate),
+        Just(PdfRemainderPolicy::Largest),
+        Just(PdfRemainderPolicy::House("southroads".to_owned())),
+    ];
+}
+
+fn amount() -> impl Strategy<Value = Decimal> {
+    return (-100_000_000i64..100_000_000).prop_map(|cents| Decimal::new(cents, 2));
+}
+
+proptest! {
+
+    #[test]
+    fn shares_add_up_to_the_amount(amount in amount(), weights in weights()) {
+        let shares = allocate(amount, &weights).unwrap();
+        prop_assert_eq!(shares.len(), weights.len());
+        prop_assert_eq!(shares.iter().sum::<Decimal>(), amount);
+    }
+
+    #[test]
+    fn shares_are_whole_cents(amount in amount(), weights in weights()) {
+        for share in allocate(amount, &weights).unwrap() {
+            prop_assert!((share * Decimal::from(100)).fract().is_zero());
+        }
+    }
+
+    #[test]
+    fn positive_amounts_give_non_negative_shares(cents in 0i64..100_000_000, weights in weights()) {
+        for share in allocate(Decimal::new(cents, 2), &weights).unwrap() {
+            prop_assert!(!share.is_sign_negative() || share.is_zero());
+        }
+    }
+
+    #[test]
+    fn shares_are_within_a_cent_of_exact(amount in amount(), weights in weights()) {
+        let total: Decimal = weights.iter().sum();
+        let shares = allocate(amount, &weights).unwrap();
+        for (share, weight) in shares.iter().zip(&weights) {
+            let exact = amount * weight / total;
+            prop_assert!((*share - exact).abs() < Decimal::new(1, 2));
+        }
+    }
+
+    #[test]
+    fn zero_weights_get_nothing(amount in amount(), weights in weights()) {
+        let shares = allocate(amount, &weights).unwrap();
+        for (share, weight) in shares.iter().zip(&weights) {
+            if weight.is_zero() {
+                prop_assert!(share.is_zero());
+            }
+        }
+    }
+
+    #[test]
+    fn allocation_is_deterministic(amount in amount(), weights in weights()) {
+        prop_assert_eq!(allocate(amount, &weights).unwrap(), allocate(amount, &weights).unwrap());
+    }
+
+    #[test]
+    fn equal_weights_hand_remainders_to_earlier_parties(amount in amount(), parties in 1usize..8) {
+        let shares = allocate(amount.abs(), &vec![Decimal::ONE; parties]).unwrap();
+        for pair in shares.windows(2) {
+            prop_assert!(pair[0] >= pair[1]);
+            prop_assert!(pair[0] - pair[1] <= Decimal::new(1, 2));
+        }
+    }
+
+    #[test]
+    fn every_policy_adds_up_to_the_amount(amount in amount(), weights in weights(), rounding in rounding(), policy in policy(), turn in 0usize..10) {
+        let parties: Vec<String> = (0..weights.len()).map(|i| if i == 1 { "southroads".to_owned() } else { format!("party{}", i) }).collect();
+        let parties: Vec<&str> = parties.iter().map(|party| party.as_str()).collect();
+        let priority = remainder_priority(&policy, &parties, &weights, turn);
+        let shares = allocate_with(amount, &weights, rounding, &priority).unwrap();
+        prop_assert_eq!(shares.iter().sum::<Decimal>(), amount);
+        let total: Decimal = weights.iter().sum();
+        for (share, weight) in shares.iter().zip(&weights) {
+            prop_assert!((*share - amount * weight / total).abs() < Decimal::new(1, 2));
+        }
+        prop_assert_eq!(shares, allocate_with(amount, &weights, rounding, &priority).unwrap());
+    }
+
+    #[test]
+    fn refunds_mirror_charges(cents in 0i64..100_000_000, weights in weights()) {
+        let charge = allocate(Decimal::new(cents, 2), &weights).unwrap();
+        let refund = allocate(Decimal::new(-cents, 2), &weights).unwrap();
+        for (charge, refund) in charge.iter().zip(&refund) {
+            prop_assert_eq!(*charge, -*refund);
+        }
+    }
+
+}
+
+#[test]
+fn odd_cents_of_a_split_go_to_utica() {
+    let shares = split_cost(Decimal::new(1001, 2), &PdfSplitOptions::default(), 0).unwrap();
+    assert_eq!(shares, vec![("utica".to_owned(), Decimal::new(501, 2)), ("southroads".to_owned(), Decimal::new(5, 0))]);
+}
+
+#[test]
+fn invalid_allocations_are_rejected() {
+    assert!(allocate(Decimal::new(1001, 3), &[Decimal::ONE]).is_err());
+    assert!(allocate(Decimal::ONE, &[]).is_err());
+    assert!(allocate(Decimal::ONE, &[Decimal::ZERO]).is_err());
+    assert!(allocate(Decimal::ONE, &[Decimal::ONE, Decimal::NEGATIVE_ONE]).is_err());
+}
+
+#[test]
+fn remainder_policies_pick_who_gets_the_odd_cent() {
+    let amount = Decimal::new(1001, 2);
+    let share_of = |remainder: PdfRemainderPolicy, turn: usize| -> Vec<Decimal> {
+        let options = PdfSplitOptions { remainder: remainder, rounding: PdfRounding::HalfUp, ..Default::default() };
+        return split_cost(amount, &options, turn).unwrap().into_iter().map(|(_, cost)| cost).collect();
+    };
+    let more = vec![Decimal::new(501, 2), Decimal::new(5, 0)];
+    let less = vec![Decimal::new(5, 0), Decimal::new(501, 2)];
+    assert_eq!(share_of(PdfRemainderPolicy::First, 1), more);
+    assert_eq!(share_of(PdfRemainderPolicy::Rotate, 0), more);
+    assert_eq!(share_of(PdfRemainderPolicy::Rotate, 1), less);
+    assert_eq!(share_of(PdfRemainderPolicy::Rotate, 2), more);
+    assert_eq!(share_of(PdfRemainderPolicy::House("southroads".to_owned()), 0), less);
+    assert_eq!("house:southroads".parse::<PdfRemainderPolicy>().unwrap(), PdfRemainderPolicy::House("southroads".to_owned()));
+    assert!("house:nowhere".parse::<PdfRemainderPolicy>().is_err());
+}
+
+#[test]
+fn rounding_decides_which_half_cents_round_up() {
+    // 0.02 over weights 1 and 3 leaves exact shares of half a cent and a cent and a half
+    let weights = [Decimal::ONE, Decimal::from(3)];
+    let half_up = allocate_with(Decimal::new(2, 2), &weights, PdfRounding::HalfUp, &[0, 1]).unwrap();
+    let bankers = allocate_with(Decimal::new(2, 2), &weights, PdfRounding::Bankers, &[0, 1]).unwrap();
+    assert_eq!(half_up, vec![Decimal::new(1, 2), Decimal::new(1, 2)]);
+    assert_eq!(bankers, vec![Decimal::ZERO, Decimal::new(2, 2)]);
+}
+
+#[test]
+fn shares_already_on_a_whole_cent_take_no_leftover() {
+    // the third share is exactly a cent, so the leftover cent goes to one of the half cents
+    let weights = [Decimal::ONE, Decimal::ONE, Decimal::from(2)];
+    for rounding in [PdfRounding::HalfUp, PdfRounding::Bankers] {
+        let shares = allocate_with(Decimal::new(2, 2), &weights, rounding, &[2, 0, 1]).unwrap();
+        assert_eq!(shares, vec![Decimal::new(1, 2), Decimal::ZERO, Decimal::new(1, 2)]);
+    }
+}
+
+#[test]
+fn split_weights_set_each_locations_share() {
+    let weighted = |remainder: PdfRemainderPolicy, rounding: PdfRounding, amount: Decimal| -> Vec<Decimal> {
+        let options = PdfSplitOptions {
+            remainder: remainder,
+            rounding: rounding,
+            weights: [("utica".to_owned(), Decimal::ONE), ("southroads".to_owned(), Decimal::from(3))].into_iter().collect(),
+        };
+        return split_cost(amount, &options, 0).unwrap().into_iter().map(|(_, cost)| cost).collect();
+    };
+    assert_eq!(weighted(PdfRemainderPolicy::First, PdfRounding::HalfUp, Decimal::new(1000, 2)), vec![Decimal::new(250, 2), Decimal::new(750, 2)]);
+    assert_eq!(weighted(PdfRemainderPolicy::First, PdfRounding::HalfUp, Decimal::new(2, 2)), vec![Decimal::new(1, 2), Decimal::new(1, 2)]);
+    assert_eq!(weighted(PdfRemainderPolicy::Largest, PdfRounding::HalfUp, Decimal::new(2, 2)), vec![Decimal::ZERO, Decimal::new(2, 2)]);
+    assert_eq!(weighted(PdfRemainderPolicy::First, PdfRounding::Bankers, Decimal::new(2, 2)), vec![Decimal::ZERO, Decimal::new(2, 2)]);
+
+    let unknown = PdfSplitOptions { weights: [("nowhere".to_owned(), Decimal::ONE)].into_iter().collect(), ..Default::default() };
+    assert!(split_cost(Decimal::ONE, &unknown, 0).is_err());
+}
@@ -0,0 +1,101 @@
+use std::fs;
+
+use finli::pdf_duplicates::{day_number, PdfDuplicateKind, PdfDuplicateReport};
+use finli::pdf_invoice::{PdfLineItem, PdfReadOptions};
+
+// a dir of receipts, each file holding its own name so none are exact duplicates
+fn receipts(name: &str, file_names: &[&str]) -> (String, Vec<PdfLineItem>) {
+    let dir = std::env::temp_dir().join(format!("finli-duplicates-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for file_name in file_names {
+        fs::write(dir.join(file_name), file_name).unwrap();
+    }
+    let dir = dir.to_string_lossy().to_string();
+    let (line_items, _) = PdfLineItem::new_from_dir(&dir, &PdfReadOptions::default()).unwrap();
+    return (dir, line_items);
+}
+
+// the file names in each group of a kind
+fn groups(report: &PdfDuplicateReport, kind: PdfDuplicateKind) -> Vec<Vec<String>> {
+    return report
+        .groups
+        .iter()
+        .filter(|group| group.kind == kind)
+        .map(|group| group.paths.iter().map(|path| path.rsplit("/").next().unwrap().to_owned()).collect())
+        .collect();
+}
+
+#[test]
+fn day_numbers_count_across_months_and_years() {
+    assert_eq!(day_number("010100"), Some(0));
+    assert_eq!(day_number("010125").unwrap() - day_number("123124").unwrap(), 1);
+    assert_eq!(day_number("030124").unwrap() - day_number("022824").unwrap(), 2);
+    assert_eq!(day_number("030125").unwrap() - day_number("022825").unwrap(), 1);
+    assert_eq!(day_number("020125").unwrap() - day_number("013125").unwrap(), 1);
+    assert_eq!(day_number("133125"), None);
+    assert_eq!(day_number("0101025"), None);
+}
+
+#[test]
+fn likely_duplicates_share_a_vendor_and_amount_within_the_window() {
+    let (_, line_items) = receipts("likely", &[
+        "123124-cintas-10.00-pants-uniforms-utica.pdf",
+        "010225-Cintas-10.00-shirts-uniforms-southroads.pdf",
+        "010325-cintas-10.01-pants-uniforms-utica.pdf",
+        "010225-staples-10.00-paper-supplies-utica.pdf",
+        "010825-cintas-10.00-pants-uniforms-utica.pdf",
+    ]);
+    let report = PdfDuplicateReport::new(&line_items, 3).unwrap();
+    assert_eq!(groups(&report, PdfDuplicateKind::Likely), vec![vec![
+        "010225-Cintas-10.00-shirts-uniforms-southroads.pdf".to_owned(),
+        "123124-cintas-10.00-pants-uniforms-utica.pdf".to_owned(),
+    ]]);
+    assert!(groups(&report, PdfDuplicateKind::Exact).is_empty());
+    assert!(report.confirmed().is_empty());
+
+    // a day short of the gap across the new year and nothing is flagged
+    let report = PdfDuplicateReport::new(&line_items, 1).unwrap();
+    assert!(report.groups.is_empty());
+}
+
+#[test]
+fn a_chain_of_close_dates_is_one_group() {
+    let (_, line_items) = receipts("transitive", &[
+        "010125-cintas-10.00-pants-uniforms-utica.pdf",
+        "010325-cintas-10.00-pants-uniforms-utica.pdf",
+        "010525-cintas-10.00-pants-uniforms-utica.pdf",
+    ]);
+    // the first and last are four days apart, but each is two days from the middle one
+    let report = PdfDuplicateReport::new(&line_items, 2).unwrap();
+    assert_eq!(groups(&report, PdfDuplicateKind::Likely), vec![vec![
+        "010125-cintas-10.00-pants-uniforms-utica.pdf".to_owned(),
+        "010325-cintas-10.00-pants-uniforms-utica.pdf".to_owned(),
+        "010525-cintas-10.00-pants-uniforms-utica.pdf".to_owned(),
+    ]]);
+}
+
+#[test]
+fn only_the_extra_copies_of_exact_duplicates_are_confirmed() {
+    let (dir, line_items) = receipts("confirmed", &[
+        "010125-cintas-10.00-pants-uniforms-utica.pdf",
+        "010125-cintas-10.00-pants_scan-uniforms-utica.pdf",
+        "010225-cintas-10.00-pants_rescan-uniforms-utica.pdf",
+        "010525-staples-3.00-paper-supplies-utica.pdf",
+    ]);
+    let copy = fs::read(format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", dir)).unwrap();
+    fs::write(format!("{}/010125-cintas-10.00-pants_scan-uniforms-utica.pdf", dir), &copy).unwrap();
+    fs::write(format!("{}/010225-cintas-10.00-pants_rescan-uniforms-utica.pdf", dir), &copy).unwrap();
+
+    let report = PdfDuplicateReport::new(&line_items, 3).unwrap();
+    assert_eq!(groups(&report, PdfDuplicateKind::Exact), vec![vec![
+        "010125-cintas-10.00-pants-uniforms-utica.pdf".to_owned(),
+        "010125-cintas-10.00-pants_scan-uniforms-utica.pdf".to_owned(),
+        "010225-cintas-10.00-pants_rescan-uniforms-utica.pdf".to_owned(),
+    ]]);
+    assert!(groups(&report, PdfDuplicateKind::Likely).is_empty());
+    assert_eq!(report.confirmed(), vec![
+        format!("{}/010125-cintas-10.00-pants_scan-uniforms-utica.pdf", dir),
+        format!("{}/010225-cintas-10.00-pants_rescan-uniforms-utica.pdf", dir),
+    ]);
+}
@@ -0,0 +1,45 @@
+use finli::pdf_period::{PdfPeriod, PdfPeriodLength};
+
+fn period(value: &str) -> PdfPeriod {
+    return value.parse::<PdfPeriod>().unwrap();
+}
+
+#[test]
+fn periods_print_the_way_they_are_written() {
+    for value in ["0125", "1224", "q125", "q424", "2025"] {
+        assert_eq!(period(value).to_string(), value);
+    }
+}
+
+#[test]
+fn invalid_periods_are_rejected() {
+    for value in ["", "13", "1325", "0025", "q525", "q025", "january", "01255"] {
+        assert!(value.parse::<PdfPeriod>().is_err(), "{} should not parse", value);
+    }
+}
+
+#[test]
+fn previous_periods_cross_year_boundaries() {
+    assert_eq!(period("0125").previous().to_string(), "1224");
+    assert_eq!(period("q125").previous().to_string(), "q424");
+    assert_eq!(period("2025").previous().to_string(), "2024");
+    assert_eq!(period("0325").year_earlier().to_string(), "0324");
+    assert_eq!(period("q325").year_earlier().to_string(), "q324");
+}
+
+#[test]
+fn periods_hold_the_dates_inside_them() {
+    assert!(period("0125").contains("013125"));
+    assert!(!period("0125").contains("020125"));
+    assert!(period("q125").contains("033125"));
+    assert!(!period("q125").contains("040125"));
+    assert!(period("2025").contains("123125"));
+    assert!(!period("2025").contains("not a date"));
+}
+
+#[test]
+fn the_latest_period_holds_the_latest_date() {
+    let latest = PdfPeriod::latest(&["010125", "121524", "bad", "020325"], PdfPeriodLength::Quarter).unwrap();
+    assert_eq!(latest.to_string(), "q125");
+    assert!(PdfPeriod::latest(&[], PdfPeriodLength::Month).is_none());
+}
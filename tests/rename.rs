@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::Path;
+
+use finli::pdf_config::PdfConfig;
+use finli::pdf_rename::{PdfRenameJournal, PdfRenamePlan};
+
+// a fresh receipt dir and journal path for one test
+fn dirs(name: &str) -> (String, String) {
+    let root = std::env::temp_dir().join(format!("finli-rename-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let dir = root.join("receipts");
+    fs::create_dir_all(&dir).unwrap();
+    return (dir.to_string_lossy().to_string(), root.join("journal.json").to_string_lossy().to_string());
+}
+
+// every file name in a dir
+fn files(dir: &str) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    return files;
+}
+
+#[test]
+fn receipts_normalizing_to_the_same_name_are_a_collision() {
+    let (dir, _) = dirs("collision");
+    fs::write(format!("{}/1.1.25-Cintas-10-pants-uniforms-utica.pdf", dir), "a").unwrap();
+    fs::write(format!("{}/01.01.25-cintas-$10.00-pants-uniforms-utica.pdf", dir), "b").unwrap();
+    let err = PdfRenamePlan::new(&dir, &PdfConfig::default(), false).err().unwrap();
+    assert!(err.contains("RENAME COLLISION:"), "{}", err);
+
+    // a receipt already sitting at the new name is one too
+    let (dir, _) = dirs("taken");
+    fs::write(format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", dir), "a").unwrap();
+    fs::write(format!("{}/010125-Cintas-10-pants-uniforms-utica.pdf", dir), "b").unwrap();
+    let err = PdfRenamePlan::new(&dir, &PdfConfig::default(), false).err().unwrap();
+    assert!(err.contains("RENAME COLLISION:"), "{}", err);
+}
+
+#[test]
+fn sidecars_are_renamed_with_their_receipts_and_undo_puts_both_back() {
+    let (dir, journal) = dirs("sidecar");
+    fs::write(format!("{}/010125-Cintas-10-pants-uniforms-utica.pdf", dir), "pants").unwrap();
+    fs::write(format!("{}/010125-Cintas-10-pants-uniforms-utica.toml", dir), "notes = \"new hires\"\n").unwrap();
+    let plan = PdfRenamePlan::new(&dir, &PdfConfig::default(), false).unwrap();
+    plan.execute(&journal).unwrap();
+    assert_eq!(files(&dir), vec!["010125-cintas-10.00-pants-uniforms-utica.pdf", "010125-cintas-10.00-pants-uniforms-utica.toml"]);
+
+    let run = PdfRenameJournal::undo(&journal, &dir).unwrap();
+    assert_eq!(run.renames.len(), 2);
+    assert_eq!(files(&dir), vec!["010125-Cintas-10-pants-uniforms-utica.pdf", "010125-Cintas-10-pants-uniforms-utica.toml"]);
+    assert_eq!(fs::read_to_string(format!("{}/010125-Cintas-10-pants-uniforms-utica.toml", dir)).unwrap(), "notes = \"new hires\"\n");
+    assert!(PdfRenameJournal::new_from_file(&journal).unwrap().runs.is_empty());
+}
+
+#[test]
+fn a_failed_rename_journals_what_it_did_so_it_can_be_undone() {
+    let (dir, journal) = dirs("partial");
+    fs::write(format!("{}/010125-Cintas-10-pants-uniforms-utica.pdf", dir), "pants").unwrap();
+    fs::write(format!("{}/010225-Staples-3-paper-supplies-utica.pdf", dir), "paper").unwrap();
+    let plan = PdfRenamePlan::new(&dir, &PdfConfig::default(), false).unwrap();
+
+    // the second receipt disappears between planning and renaming
+    fs::remove_file(format!("{}/010225-Staples-3-paper-supplies-utica.pdf", dir)).unwrap();
+    let err = plan.execute(&journal).err().unwrap();
+    assert!(err.contains("RENAME FAILURE:"), "{}", err);
+    assert_eq!(files(&dir), vec!["010125-cintas-10.00-pants-uniforms-utica.pdf"]);
+    let runs = PdfRenameJournal::new_from_file(&journal).unwrap().runs;
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].renames.len(), 1);
+
+    PdfRenameJournal::undo(&journal, &dir).unwrap();
+    assert_eq!(files(&dir), vec!["010125-Cintas-10-pants-uniforms-utica.pdf"]);
+    assert!(Path::new(&format!("{}/010125-Cintas-10-pants-uniforms-utica.pdf", dir)).is_file());
+}
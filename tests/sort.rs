@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::Path;
+
+use finli::pdf_check::PdfCheckReport;
+use finli::pdf_invoice::{PdfLineItem, PdfReadOptions};
+use finli::pdf_sort_plan::{PdfConflictPolicy, PdfSortMode, PdfSortOptions};
+use finli::pdf_sort_summary::PdfSortSummary;
+use finli::pdf_sorted_dir::PdfSortedDir;
+use finli::pdf_verify::PdfVerifyOptions;
+
+// a fresh inbox and output dir for one test
+fn dirs(name: &str) -> (String, String) {
+    let root = std::env::temp_dir().join(format!("finli-sort-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let inbox = root.join("inbox");
+    fs::create_dir_all(&inbox).unwrap();
+    return (inbox.to_string_lossy().to_string(), root.join("out").to_string_lossy().to_string());
+}
+
+fn write(path: &str, contents: &str) {
+    fs::create_dir_all(Path::new(path).parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+// every file under a dir, relative to it
+fn files(dir: &str) -> Vec<String> {
+    let mut files: Vec<String> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.path().strip_prefix(dir).unwrap().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    return files;
+}
+
+#[test]
+fn split_shares_are_named_from_the_fields_a_sidecar_overrides() {
+    let (inbox, out) = dirs("sidecar-split");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox), "pants");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.toml", inbox), "location = \"split\"\ncost = \"12.00\"\n");
+    let options = PdfSortOptions { layout: "{year}".to_owned(), ..Default::default() };
+    PdfSortedDir::new(&inbox, &out, &options).unwrap();
+    let receipts: Vec<String> = files(&out).into_iter().filter(|file| !PdfSortSummary::is_summary_path(Path::new(file))).collect();
+    assert_eq!(receipts, vec![
+        "2025/010125-cintas-6-pants-uniforms-southroads.pdf",
+        "2025/010125-cintas-6-pants-uniforms-southroads.toml",
+        "2025/010125-cintas-6-pants-uniforms-utica.pdf",
+        "2025/010125-cintas-6-pants-uniforms-utica.toml",
+        "finli-manifest.json",
+    ]);
+}
+
+#[test]
+fn overwrite_never_replaces_a_receipt_from_the_same_sort() {
+    let (inbox, out) = dirs("overwrite-collision");
+    write(&format!("{}/a/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox), "a");
+    write(&format!("{}/b/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox), "b");
+    let options = PdfSortOptions {
+        mode: PdfSortMode::Move,
+        on_conflict: PdfConflictPolicy::Overwrite,
+        read: PdfReadOptions { recursive: true, ..Default::default() },
+        ..Default::default()
+    };
+    let sorted = PdfSortedDir::new(&inbox, &out, &options).unwrap();
+    assert!(files(&inbox).is_empty());
+    let mut contents: Vec<String> = files(&out)
+        .iter()
+        .filter(|file| file.ends_with("-utica.pdf"))
+        .map(|file| fs::read_to_string(format!("{}/{}", out, file)).unwrap())
+        .collect();
+    contents.sort();
+    assert_eq!(contents, vec!["a", "b"]);
+    assert_eq!(sorted.summary.locations[0].count, 2);
+}
+
+#[test]
+fn a_move_keeps_a_split_receipt_when_a_share_is_skipped() {
+    let (inbox, out) = dirs("skip-split");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-split.pdf", inbox), "pants");
+    write(&format!("{}/southroads/010125-cintas-5-pants-uniforms-southroads.pdf", out), "something else");
+    let options = PdfSortOptions { mode: PdfSortMode::Move, on_conflict: PdfConflictPolicy::Skip, ..Default::default() };
+    let sorted = PdfSortedDir::new(&inbox, &out, &options).unwrap();
+    assert_eq!(files(&inbox), vec!["010125-cintas-10.00-pants-uniforms-split.pdf"]);
+    assert_eq!(sorted.kept, vec![format!("{}/010125-cintas-10.00-pants-uniforms-split.pdf", inbox)]);
+    assert_eq!(fs::read_to_string(format!("{}/southroads/010125-cintas-5-pants-uniforms-southroads.pdf", out)).unwrap(), "something else");
+    assert_eq!(fs::read_to_string(format!("{}/utica/010125-cintas-5-pants-uniforms-utica.pdf", out)).unwrap(), "pants");
+}
+
+#[test]
+fn a_failed_sort_puts_the_output_back_how_it_was() {
+    let (inbox, out) = dirs("rollback");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-split.pdf", inbox), "pants");
+    write(&format!("{}/southroads/010125-cintas-5-pants-uniforms-southroads.pdf", out), "before");
+    // not a real image, so converting it fails after the split shares are written
+    write(&format!("{}/020125-staples-3.00-paper-supplies-utica.png", inbox), "not a png");
+    let options = PdfSortOptions { on_conflict: PdfConflictPolicy::Overwrite, convert_images: true, ..Default::default() };
+    let err = PdfSortedDir::new(&inbox, &out, &options).err().unwrap();
+    assert!(err.contains("ROLLBACK:"), "{}", err);
+    assert_eq!(files(&out), vec!["southroads/010125-cintas-5-pants-uniforms-southroads.pdf"]);
+    assert_eq!(fs::read_to_string(format!("{}/southroads/010125-cintas-5-pants-uniforms-southroads.pdf", out)).unwrap(), "before");
+    assert!(!Path::new(&format!("{}/utica", out)).exists());
+    assert_eq!(files(&inbox).len(), 2);
+}
+
+#[test]
+fn a_second_sort_only_handles_new_and_changed_receipts() {
+    let (inbox, out) = dirs("incremental");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox), "pants");
+    write(&format!("{}/010225-staples-3.00-paper-supplies-southroads.pdf", inbox), "paper");
+    let first = PdfSortedDir::new(&inbox, &out, &PdfSortOptions::default()).unwrap();
+
+    // summaries are named by the second a sort ran in
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    write(&format!("{}/010325-cintas-4.00-shirts-uniforms-utica.pdf", inbox), "shirts");
+    write(&format!("{}/010225-staples-3.00-paper-supplies-southroads.pdf", inbox), "more paper");
+    let sorted = PdfSortedDir::new(&inbox, &out, &PdfSortOptions::default()).unwrap();
+    assert_eq!(sorted.unchanged, vec![format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox)]);
+    let replaced: Vec<&str> = sorted.conflicts.iter().map(|conflict| conflict.resolution.as_str()).collect();
+    assert_eq!(replaced, vec!["replaced"]);
+    assert_eq!(fs::read_to_string(format!("{}/southroads/010225-staples-3.00-paper-supplies-southroads.pdf", out)).unwrap(), "more paper");
+    assert!(Path::new(&format!("{}/utica/010325-cintas-4.00-shirts-uniforms-utica.pdf", out)).is_file());
+
+    // the first run's summaries are still there next to the second's
+    assert_eq!(first.summary_files.len(), 4);
+    assert_eq!(sorted.summary_files.len(), 4);
+    for path in first.summary_files.iter().chain(&sorted.summary_files) {
+        assert!(Path::new(path).is_file(), "{}", path);
+    }
+}
+
+#[test]
+fn a_sorted_dir_reads_back_without_its_manifest() {
+    let (inbox, out) = dirs("read-back");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-split.pdf", inbox), "pants");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-split.toml", inbox), "notes = \"for the new hires\"\n");
+    let options = PdfSortOptions { layout: String::new(), ..Default::default() };
+    PdfSortedDir::new(&inbox, &out, &options).unwrap();
+    assert!(Path::new(&format!("{}/finli-manifest.json", out)).is_file());
+
+    let (line_items, invalid) = PdfLineItem::new_from_dir(&out, &PdfReadOptions::default()).unwrap();
+    assert_eq!(line_items.len(), 2);
+    assert!(invalid.is_empty());
+    let report = PdfCheckReport::new(&out, false, &PdfVerifyOptions::default()).unwrap();
+    assert!(report.issues.iter().all(|issue| !issue.path.ends_with("finli-manifest.json")), "{:?}", report.issues);
+}
+
+#[test]
+fn a_sidecar_already_at_the_destination_is_a_conflict() {
+    let (inbox, out) = dirs("sidecar-conflict");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox), "pants");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.toml", inbox), "notes = \"new\"\n");
+    write(&format!("{}/utica/010125-cintas-10.00-pants-uniforms-utica.toml", out), "notes = \"old\"\n");
+
+    let err = PdfSortedDir::new(&inbox, &out, &PdfSortOptions::default()).err().unwrap();
+    assert!(err.contains("SORT CONFLICT:"), "{}", err);
+    assert_eq!(files(&out), vec!["utica/010125-cintas-10.00-pants-uniforms-utica.toml"]);
+    assert_eq!(fs::read_to_string(format!("{}/utica/010125-cintas-10.00-pants-uniforms-utica.toml", out)).unwrap(), "notes = \"old\"\n");
+
+    let options = PdfSortOptions { on_conflict: PdfConflictPolicy::Rename, ..Default::default() };
+    PdfSortedDir::new(&inbox, &out, &options).unwrap();
+    assert_eq!(fs::read_to_string(format!("{}/utica/010125-cintas-10.00-pants-uniforms-utica.toml", out)).unwrap(), "notes = \"old\"\n");
+    assert_eq!(fs::read_to_string(format!("{}/utica/010125-cintas-10.00-pants_2-uniforms-utica.toml", out)).unwrap(), "notes = \"new\"\n");
+    assert!(Path::new(&format!("{}/utica/010125-cintas-10.00-pants_2-uniforms-utica.pdf", out)).is_file());
+}
+
+#[test]
+fn receipts_are_named_and_laid_out_from_the_fields_a_sidecar_overrides() {
+    let (inbox, out) = dirs("sidecar-rename");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.pdf", inbox), "pants");
+    write(&format!("{}/010125-cintas-10.00-pants-uniforms-utica.toml", inbox), "location = \"southroads\"\ncost = \"12.00\"\ndate = \"020326\"\n");
+    let options = PdfSortOptions { layout: "{year}/{location}".to_owned(), ..Default::default() };
+    PdfSortedDir::new(&inbox, &out, &options).unwrap();
+    let receipts: Vec<String> = files(&out).into_iter().filter(|file| !PdfSortSummary::is_summary_path(Path::new(file))).collect();
+    assert_eq!(receipts, vec![
+        "2026/southroads/020326-cintas-12.00-pants-uniforms-southroads.pdf",
+        "2026/southroads/020326-cintas-12.00-pants-uniforms-southroads.toml",
+        "finli-manifest.json",
+    ]);
+}